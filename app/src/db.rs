@@ -1,4 +1,4 @@
-use std::time::Duration;
+use std::{sync::Arc, time::Duration};
 
 use hub_core::{anyhow::Result, clap, prelude::*};
 pub use sea_orm::{ConnectOptions, Database, DatabaseConnection};
@@ -18,8 +18,10 @@ pub struct DbArgs {
     pub database_url: String,
 }
 
+/// A shared database connection. The connection is behind an `Arc` because the mock
+/// connection used in tests is not `Clone`.
 #[derive(Debug, Clone)]
-pub struct Connection(DatabaseConnection);
+pub struct Connection(Arc<DatabaseConnection>);
 
 impl Connection {
     /// Res
@@ -46,7 +48,7 @@ impl Connection {
             .await
             .context("failed to get database connection")?;
 
-        Ok(Self(connection))
+        Ok(Self(Arc::new(connection)))
     }
 
     #[must_use]
@@ -54,4 +56,17 @@ impl Connection {
     pub fn get(&self) -> &DatabaseConnection {
         &self.0
     }
+
+    /// The underlying connection, once every clone of this one has been dropped
+    #[cfg(test)]
+    #[must_use]
+    pub fn into_inner(self) -> Option<DatabaseConnection> {
+        Arc::try_unwrap(self.0).ok()
+    }
+}
+
+impl From<DatabaseConnection> for Connection {
+    fn from(connection: DatabaseConnection) -> Self {
+        Self(Arc::new(connection))
+    }
 }
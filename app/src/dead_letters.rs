@@ -0,0 +1,213 @@
+use hub_core::{chrono::Utc, prelude::*};
+use sea_orm::{prelude::*, QueryOrder, QuerySelect, Set};
+
+use crate::{
+    db::Connection,
+    entities::dead_letters,
+    events::{self, MissingParent},
+    journal, pending, Envelope, Metadata,
+};
+
+/// Outcome of a [`retry`] run
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Retried {
+    pub succeeded: u64,
+    pub parked: u64,
+    pub failed: u64,
+}
+
+/// Records a message that failed to decode or process. A message that is already dead lettered
+/// has its attempt count bumped and its error replaced with the latest one.
+///
+/// # Errors
+/// This function fails if the dead letter cannot be written
+pub async fn record(db: &Connection, msg: &Envelope, error: &Error) -> Result<()> {
+    let Metadata {
+        topic,
+        partition,
+        offset,
+        timestamp,
+    } = msg.metadata.clone();
+    let now = Utc::now().naive_utc();
+
+    let existing = dead_letters::Entity::find_by_id((topic.clone(), partition, offset))
+        .one(db.get())
+        .await?;
+
+    if let Some(letter) = existing {
+        let attempts = letter.attempts + 1;
+        let mut letter: dead_letters::ActiveModel = letter.into();
+
+        letter.error = Set(format!("{error:#}"));
+        letter.attempts = Set(attempts);
+        letter.updated_at = Set(now);
+
+        letter.update(db.get()).await?;
+    } else {
+        dead_letters::ActiveModel {
+            topic: Set(topic),
+            partition: Set(partition),
+            offset: Set(offset),
+            key: Set(msg.key.clone()),
+            payload: Set(msg.payload.clone()),
+            error: Set(format!("{error:#}")),
+            attempts: Set(1),
+            timestamp: Set(timestamp),
+            created_at: Set(now),
+            updated_at: Set(now),
+        }
+        .insert(db.get())
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Journals and retries up to `limit` dead letters through [`events::process`], least attempted
+/// first so letters that keep failing do not hold back the others. Letters that succeed are
+/// removed, letters missing a parent or behind a parked message of their key are moved to the
+/// [`pending`] events and the rest are recorded again with their new error.
+///
+/// # Errors
+/// This function fails if the dead letters cannot be read or updated
+pub async fn retry(db: &Connection, limit: u64) -> Result<Retried> {
+    let letters = dead_letters::Entity::find()
+        .order_by_asc(dead_letters::Column::Attempts)
+        .order_by_asc(dead_letters::Column::CreatedAt)
+        .limit(limit)
+        .all(db.get())
+        .await?;
+
+    let mut retried = Retried::default();
+
+    for letter in letters {
+        let msg = Envelope::from(letter.clone());
+
+        let res = match journal::append(db, &msg).await {
+            Ok(()) => process(db, &msg).await,
+            Err(e) => Err(e.context("failed to journal message")),
+        };
+
+        match res {
+            Ok(()) => {
+                letter.delete(db.get()).await?;
+                retried.succeeded += 1;
+            },
+            Err(e) => {
+                if let Some(missing) = e.downcast_ref::<MissingParent>() {
+                    pending::park(db, &msg, missing).await?;
+                    letter.delete(db.get()).await?;
+                    retried.parked += 1;
+                } else {
+                    warn!(
+                        topic = msg.metadata.topic,
+                        offset = msg.metadata.offset,
+                        "dead letter retry failed: {e:#}"
+                    );
+                    record(db, &msg, &e).await?;
+                    retried.failed += 1;
+                }
+            },
+        }
+    }
+
+    Ok(retried)
+}
+
+/// Processes `msg` unless an earlier message of its key is parked, failing with the parent that
+/// message is waiting on so `msg` is parked behind it
+async fn process(db: &Connection, msg: &Envelope) -> Result<()> {
+    if let Some(missing) = pending::blocking(db, msg).await? {
+        bail!(missing);
+    }
+
+    events::process(msg, db.clone()).await
+}
+
+impl From<dead_letters::Model> for Envelope {
+    fn from(letter: dead_letters::Model) -> Self {
+        let dead_letters::Model {
+            topic,
+            partition,
+            offset,
+            key,
+            payload,
+            timestamp,
+            ..
+        } = letter;
+
+        Self {
+            metadata: Metadata {
+                topic,
+                partition,
+                offset,
+                timestamp,
+            },
+            key,
+            payload,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hub_core::tokio;
+    use sea_orm::MockExecResult;
+
+    use super::retry;
+    use crate::{
+        db::Connection,
+        entities::{dead_letters, pending_events},
+        events::test::*,
+    };
+
+    const CREDIT: &str = "7eb0a199-d034-41e2-9264-c4d16083ef76";
+
+    #[tokio::test(crate = "hub_core::tokio")]
+    async fn letter_behind_a_parked_message_of_its_key_is_parked_unprocessed() {
+        let (earlier, later) = (purchase(CREDIT, 0), purchase(CREDIT, 1));
+        let now = timestamp();
+        let parked = pending_events::Model {
+            topic: earlier.metadata.topic,
+            partition: earlier.metadata.partition,
+            offset: earlier.metadata.offset,
+            key: earlier.key,
+            payload: earlier.payload,
+            parent_table: "organizations".to_string(),
+            parent_id: ORGANIZATION.parse().unwrap(),
+            timestamp: now,
+            created_at: now,
+        };
+        let letter = dead_letters::Model {
+            topic: later.metadata.topic,
+            partition: later.metadata.partition,
+            offset: later.metadata.offset,
+            key: later.key,
+            payload: later.payload,
+            error: "connection reset".to_string(),
+            attempts: 1,
+            timestamp: now,
+            created_at: now,
+            updated_at: now,
+        };
+        let db: Connection = mock()
+            .append_query_results([[letter]])
+            .append_exec_results([MockExecResult::default()])
+            .append_query_results([[parked]])
+            .append_exec_results([MockExecResult::default(), MockExecResult::default()])
+            .into_connection()
+            .into();
+
+        let retried = retry(&db, 10).await.unwrap();
+
+        assert_eq!(
+            (retried.succeeded, retried.parked, retried.failed),
+            (0, 1, 0)
+        );
+
+        let log = db.into_inner().unwrap().into_transaction_log();
+        assert!(ran(&log, r#"INSERT INTO "pending_events""#));
+        assert!(ran(&log, r#"DELETE FROM "dead_letters""#));
+        assert!(!ran(&log, r#"FROM "organizations""#));
+    }
+}
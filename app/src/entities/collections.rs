@@ -13,6 +13,12 @@ pub struct Model {
     #[sea_orm(column_type = "Text")]
     pub blockchain: String,
     pub timestamp: DateTime,
+    #[sea_orm(column_type = "Text")]
+    pub status: String,
+    pub supply: Option<i64>,
+    pub seller_fee_basis_points: Option<i32>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub creator: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
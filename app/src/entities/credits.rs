@@ -7,8 +7,10 @@ use sea_orm::entity::prelude::*;
 pub struct Model {
     #[sea_orm(primary_key)]
     pub id: Uuid,
-    pub amount: u64,
+    pub amount: i64,
     pub organization_id: Uuid,
+    #[sea_orm(column_type = "Text")]
+    pub action: String,
     pub timestamp: DateTime,
 }
 
@@ -9,6 +9,7 @@ pub struct Model {
     pub id: Uuid,
     pub project_id: Uuid,
     pub timestamp: DateTime,
+    pub deleted_at: Option<DateTime>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
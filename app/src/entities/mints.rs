@@ -10,6 +10,8 @@ pub struct Model {
     pub project_id: Uuid,
     pub collection_id: Uuid,
     pub timestamp: DateTime,
+    #[sea_orm(column_type = "Text")]
+    pub status: String,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -2,12 +2,23 @@
 
 pub mod prelude;
 
+pub mod collection_history;
 pub mod collections;
+pub mod consumer_offsets;
+pub mod credentials;
 pub mod credits;
 pub mod customers;
+pub mod dead_letters;
+pub mod event_log;
+pub mod invites;
+pub mod member_status_changes;
+pub mod members;
+pub mod mint_status_changes;
 pub mod mints;
 pub mod organizations;
+pub mod pending_events;
 pub mod projects;
 pub mod transfers;
+pub mod treasuries;
 pub mod wallets;
 pub mod webhooks;
@@ -11,6 +11,7 @@ pub struct Model {
     pub name: String,
     pub organization_id: Uuid,
     pub timestamp: DateTime,
+    pub deactivated_at: Option<DateTime>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
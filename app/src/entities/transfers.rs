@@ -9,6 +9,12 @@ pub struct Model {
     pub id: Uuid,
     pub project_id: Uuid,
     pub timestamp: DateTime,
+    pub mint_id: Option<Uuid>,
+    pub collection_id: Option<Uuid>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub sender: Option<String>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub recipient: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -8,9 +8,12 @@ pub struct Model {
     #[sea_orm(primary_key)]
     pub id: Uuid,
     pub project_id: Uuid,
+    pub customer_id: Option<Uuid>,
     #[sea_orm(column_type = "Text")]
     pub blockchain: String,
     pub timestamp: DateTime,
+    #[sea_orm(column_type = "Text")]
+    pub kind: String,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -10,6 +10,7 @@ pub struct Model {
     pub project_id: Uuid,
     pub organization_id: Uuid,
     pub timestamp: DateTime,
+    pub deleted_at: Option<DateTime>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -0,0 +1,126 @@
+//! API credentials of an organization, optionally limited to a project
+
+use hub_core::prelude::*;
+use poem::async_trait;
+use sea_orm::{prelude::*, sea_query::Expr, Set};
+
+use super::{parse_id, EventContext, EventHandler, Owner};
+use crate::{
+    entities::{credentials, organizations, projects},
+    journal::{messages, oneofs},
+    proto::{credential_events::Event, Credential, CredentialEventKey as Key, CredentialEvents},
+};
+
+/// API credentials of an organization, optionally limited to a project
+pub struct Credentials;
+
+#[async_trait]
+impl EventHandler for Credentials {
+    const TOPIC: &'static str = "hub-credentials";
+    const EVENTS: &'static [&'static str] = &["Created", "Deleted"];
+
+    type Key = Key;
+    type Event = CredentialEvents;
+
+    fn event(v: &Self::Event) -> Option<&'static str> {
+        match &v.event {
+            Some(Event::Created(_)) => Some("Created"),
+            Some(Event::Deleted(_)) => Some("Deleted"),
+            _ => None,
+        }
+    }
+
+    fn owner(_: &Key, v: &Self::Event) -> Owner {
+        match &v.event {
+            Some(Event::Created(c) | Event::Deleted(c)) => {
+                Owner::new(&c.organization_id, &c.project_id)
+            },
+            _ => Owner::default(),
+        }
+    }
+
+    async fn handle(&self, cx: &EventContext, k: Key, v: Self::Event) -> Result<()> {
+        match v.event {
+            Some(Event::Created(v)) => created(cx, k, v).await,
+            Some(Event::Deleted(v)) => deleted(cx, k, v).await,
+            _ => Ok(()),
+        }
+    }
+}
+
+messages! {
+    CredentialEventKey { id },
+    CredentialEvents { event },
+    Credential { organization_id, project_id },
+}
+
+oneofs! {
+    credential_events { Created, Deleted },
+}
+
+async fn created(cx: &EventContext, k: Key, v: Credential) -> Result<()> {
+    let project_id = if v.project_id.is_empty() {
+        None
+    } else {
+        Some(cx.parent::<projects::Entity>(&v.project_id).await?)
+    };
+
+    cx.upsert(
+        credentials::ActiveModel {
+            id: Set(parse_id(&k.id)?),
+            organization_id: Set(cx
+                .parent::<organizations::Entity>(&v.organization_id)
+                .await?),
+            project_id: Set(project_id),
+            timestamp: Set(cx.timestamp),
+            deleted_at: Set(None),
+        },
+        &[],
+    )
+    .await
+}
+
+/// Only sets the deletion time, a credential deleted before its creation was processed waits
+/// for it rather than being recorded as created at the time of deletion
+async fn deleted(cx: &EventContext, k: Key, _: Credential) -> Result<()> {
+    let credential = cx.find::<credentials::Entity>(&k.id).await?;
+
+    credentials::Entity::update_many()
+        .col_expr(
+            credentials::Column::DeletedAt,
+            Expr::value(Some(cx.timestamp)),
+        )
+        .filter(credentials::Column::Id.eq(credential.id))
+        .exec(cx.db.get())
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use hub_core::tokio;
+
+    use super::{deleted, Credential, Key};
+    use crate::{
+        entities::credentials,
+        events::{test::*, MissingParent},
+    };
+
+    const CREDENTIAL: &str = "7e1ab299-d034-4f2e-b264-c4d16083ef76";
+
+    #[tokio::test(crate = "hub_core::tokio")]
+    async fn deleted_before_created_is_missing_parent() {
+        let cx = context(mock().append_query_results([Vec::<credentials::Model>::new()]));
+        let key = Key {
+            id: CREDENTIAL.to_string(),
+        };
+
+        let err = deleted(&cx, key, Credential::default()).await.unwrap_err();
+        let missing = err.downcast_ref::<MissingParent>().unwrap();
+
+        assert_eq!(missing.table, "credentials");
+        assert_eq!(missing.id.to_string(), CREDENTIAL);
+        assert_eq!(log(cx).len(), 1);
+    }
+}
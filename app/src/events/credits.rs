@@ -0,0 +1,89 @@
+//! Credits purchased and spent by an organization
+
+use hub_core::prelude::*;
+use poem::async_trait;
+use sea_orm::Set;
+
+use super::{int_to_action, parse_id, EventContext, EventHandler, Owner};
+use crate::{
+    entities::{credits, organizations},
+    journal::{messages, oneofs},
+    proto::{
+        credits_event::Event, CreditDeduction, CreditPurchase, CreditsEvent, CreditsEventKey as Key,
+    },
+};
+
+/// Credits purchased and spent by an organization
+pub struct Credits;
+
+#[async_trait]
+impl EventHandler for Credits {
+    const TOPIC: &'static str = "hub-credits";
+    const EVENTS: &'static [&'static str] = &["CreditsDeducted", "CreditsPurchased"];
+
+    type Key = Key;
+    type Event = CreditsEvent;
+
+    fn event(v: &Self::Event) -> Option<&'static str> {
+        match &v.event {
+            Some(Event::CreditsDeducted(_)) => Some("CreditsDeducted"),
+            Some(Event::CreditsPurchased(_)) => Some("CreditsPurchased"),
+            _ => None,
+        }
+    }
+
+    fn owner(_: &Key, v: &Self::Event) -> Owner {
+        match &v.event {
+            Some(Event::CreditsDeducted(d)) => Owner::organization(&d.organization_id),
+            Some(Event::CreditsPurchased(p)) => Owner::organization(&p.organization_id),
+            _ => Owner::default(),
+        }
+    }
+
+    async fn handle(&self, cx: &EventContext, k: Key, v: Self::Event) -> Result<()> {
+        match v.event {
+            Some(Event::CreditsDeducted(v)) => deducted(cx, k, v).await,
+            Some(Event::CreditsPurchased(v)) => purchased(cx, k, v).await,
+            _ => Ok(()),
+        }
+    }
+}
+
+messages! {
+    CreditsEventKey { id, user_id },
+    CreditsEvent { event },
+    CreditDeduction { organization_id, amount, action },
+    CreditPurchase { organization_id, amount },
+}
+
+oneofs! {
+    credits_event { CreditsDeducted, CreditsPurchased },
+}
+
+async fn deducted(cx: &EventContext, k: Key, v: CreditDeduction) -> Result<()> {
+    insert(cx, k, v.organization_id, v.amount, int_to_action(v.action)).await
+}
+
+async fn purchased(cx: &EventContext, k: Key, v: CreditPurchase) -> Result<()> {
+    insert(cx, k, v.organization_id, v.amount, "PURCHASE".to_string()).await
+}
+
+async fn insert(
+    cx: &EventContext,
+    k: Key,
+    organization_id: String,
+    amount: u64,
+    action: String,
+) -> Result<()> {
+    cx.upsert(
+        credits::ActiveModel {
+            id: Set(parse_id(&k.id)?),
+            organization_id: Set(cx.parent::<organizations::Entity>(&organization_id).await?),
+            amount: Set(amount.try_into()?),
+            action: Set(action),
+            timestamp: Set(cx.timestamp),
+        },
+        &[],
+    )
+    .await
+}
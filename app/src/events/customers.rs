@@ -0,0 +1,189 @@
+//! Customers of a project
+
+use hub_core::prelude::*;
+use poem::async_trait;
+use sea_orm::{prelude::*, sea_query::Expr, Set};
+
+use super::{parse_id, EventContext, EventHandler, Owner};
+use crate::{
+    entities::{customers, projects},
+    journal::{messages, oneofs},
+    proto::{customer_events::Event, Customer, CustomerEventKey as Key, CustomerEvents},
+};
+
+/// Customers of a project
+pub struct Customers;
+
+#[async_trait]
+impl EventHandler for Customers {
+    const TOPIC: &'static str = "hub-customers";
+    const EVENTS: &'static [&'static str] = &["Created", "Deleted"];
+
+    type Key = Key;
+    type Event = CustomerEvents;
+
+    fn event(v: &Self::Event) -> Option<&'static str> {
+        match &v.event {
+            Some(Event::Created(_)) => Some("Created"),
+            Some(Event::Deleted(_)) => Some("Deleted"),
+            _ => None,
+        }
+    }
+
+    fn owner(k: &Key, _: &Self::Event) -> Owner {
+        Owner::project(&k.project_id)
+    }
+
+    async fn handle(&self, cx: &EventContext, k: Key, v: Self::Event) -> Result<()> {
+        match v.event {
+            Some(Event::Created(v)) => created(cx, k, v).await,
+            Some(Event::Deleted(v)) => deleted(cx, k, v).await,
+            _ => Ok(()),
+        }
+    }
+}
+
+messages! {
+    CustomerEventKey { id, project_id },
+    CustomerEvents { event },
+    Customer { project_id },
+}
+
+oneofs! {
+    customer_events { Created, Deleted },
+}
+
+async fn created(cx: &EventContext, k: Key, v: Customer) -> Result<()> {
+    cx.upsert(
+        customers::ActiveModel {
+            id: Set(parse_id(&k.id)?),
+            project_id: Set(cx.parent::<projects::Entity>(&v.project_id).await?),
+            timestamp: Set(cx.timestamp),
+            deleted_at: Set(None),
+        },
+        &[],
+    )
+    .await
+}
+
+/// Only sets the deletion time, a customer deleted before its creation was processed waits for
+/// it rather than being recorded as created at the time of deletion
+async fn deleted(cx: &EventContext, k: Key, _: Customer) -> Result<()> {
+    let customer = cx.find::<customers::Entity>(&k.id).await?;
+
+    customers::Entity::update_many()
+        .col_expr(
+            customers::Column::DeletedAt,
+            Expr::value(Some(cx.timestamp)),
+        )
+        .filter(customers::Column::Id.eq(customer.id))
+        .exec(cx.db.get())
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use hub_core::tokio;
+    use sea_orm::MockExecResult;
+
+    use super::{created, deleted, Customer, DateTime, Key};
+    use crate::{
+        entities::{customers, projects},
+        events::{test::*, MissingParent},
+    };
+
+    const CUSTOMER: &str = "5c9e8f77-be12-4fc0-b042-a2bf4e61cd54";
+
+    fn event() -> (Key, Customer) {
+        let key = Key {
+            id: CUSTOMER.to_string(),
+            project_id: PROJECT.to_string(),
+        };
+        let customer = Customer {
+            project_id: PROJECT.to_string(),
+        };
+
+        (key, customer)
+    }
+
+    #[tokio::test(crate = "hub_core::tokio")]
+    async fn created_ignores_redelivery() {
+        let cx = context(
+            mock()
+                .append_query_results([[project()]])
+                .append_exec_results([MockExecResult::default()]),
+        );
+        let (k, v) = event();
+
+        created(&cx, k, v).await.unwrap();
+
+        assert_eq!(log(cx), [
+            find_project(),
+            statement(
+                r#"INSERT INTO "customers" ("id", "project_id", "timestamp", "deleted_at") VALUES ($1, $2, $3, $4) ON CONFLICT ("id") DO NOTHING"#,
+                [
+                    id(CUSTOMER),
+                    id(PROJECT),
+                    timestamp().into(),
+                    None::<DateTime>.into(),
+                ],
+            ),
+        ]);
+    }
+
+    #[tokio::test(crate = "hub_core::tokio")]
+    async fn deleted_only_updates_deletion_time() {
+        let cx = context(
+            mock()
+                .append_query_results([[customers::Model {
+                    id: CUSTOMER.parse().unwrap(),
+                    project_id: PROJECT.parse().unwrap(),
+                    timestamp: timestamp(),
+                    deleted_at: None,
+                }]])
+                .append_exec_results([MockExecResult::default()]),
+        );
+        let (k, v) = event();
+
+        deleted(&cx, k, v).await.unwrap();
+
+        assert_eq!(log(cx), [
+            statement(
+                r#"SELECT "customers"."id", "customers"."project_id", "customers"."timestamp", "customers"."deleted_at" FROM "customers" WHERE "customers"."id" = $1 LIMIT $2"#,
+                [id(CUSTOMER), 1u64.into()],
+            ),
+            statement(
+                r#"UPDATE "customers" SET "deleted_at" = $1 WHERE "customers"."id" = $2"#,
+                [Some(timestamp()).into(), id(CUSTOMER)],
+            ),
+        ]);
+    }
+
+    #[tokio::test(crate = "hub_core::tokio")]
+    async fn deleted_before_created_is_missing_parent() {
+        let cx = context(mock().append_query_results([Vec::<customers::Model>::new()]));
+        let (k, v) = event();
+
+        let err = deleted(&cx, k, v).await.unwrap_err();
+        let missing = err.downcast_ref::<MissingParent>().unwrap();
+
+        assert_eq!(missing.table, "customers");
+        assert_eq!(missing.id.to_string(), CUSTOMER);
+        assert_eq!(log(cx).len(), 1);
+    }
+
+    #[tokio::test(crate = "hub_core::tokio")]
+    async fn created_before_project_is_missing_parent() {
+        let cx = context(mock().append_query_results([Vec::<projects::Model>::new()]));
+        let (k, v) = event();
+
+        let err = created(&cx, k, v).await.unwrap_err();
+        let missing = err.downcast_ref::<MissingParent>().unwrap();
+
+        assert_eq!(missing.table, "projects");
+        assert_eq!(missing.id.to_string(), PROJECT);
+        assert_eq!(log(cx), [find_project()]);
+    }
+}
@@ -0,0 +1,546 @@
+mod credentials;
+mod credits;
+mod customers;
+mod nfts;
+mod organizations;
+mod polygon_nfts;
+mod registry;
+mod solana_nfts;
+mod treasuries;
+mod webhooks;
+
+use std::fmt::Debug;
+
+use hub_core::{prelude::*, thiserror, uuid::Uuid};
+use poem::async_trait;
+use sea_orm::{
+    prelude::*,
+    sea_query::{Expr, OnConflict},
+    Condition, IntoActiveModel, Iterable, QuerySelect, QueryTrait, Set,
+};
+
+pub use self::registry::{registry, Decoded, HandlerMetrics, Registry, TOPICS};
+use crate::{
+    db::Connection,
+    entities::{collection_history, collections, mint_status_changes, mints, projects, transfers},
+    journal::ToJson,
+    proto::{Action, CreationStatus},
+    Envelope,
+};
+
+/// A projection refers to a parent row whose event has not been processed yet, usually because
+/// it was published on another topic. Messages failing with this error are parked by
+/// [`pending`](crate::pending) until the parent shows up.
+#[derive(Debug, thiserror::Error)]
+#[error("{table} {id} has not been recorded yet")]
+pub struct MissingParent {
+    pub table: String,
+    pub id: Uuid,
+}
+
+/// Applies `msg` to the projections with the [`EventHandler`] registered for its topic.
+///
+/// Projections are written with [`EventContext::upsert`] so a redelivered or replayed message
+/// leaves the tables as they were after the first delivery. Timestamps are the event time
+/// resolved in [`Metadata`](crate::Metadata), never the time the message happened to be
+/// processed.
+///
+/// # Errors
+/// This function fails if no handler is registered for the topic, the message cannot be decoded
+/// or a projection cannot be written
+pub async fn process(msg: &Envelope, db: Connection) -> Result<()> {
+    registry().process(msg, &db).await
+}
+
+/// Applies the messages of one hub topic to the projections. The [`Registry`] decodes each
+/// message into the handler's key and event before calling it, and journals and scopes it
+/// through the handler too, so a topic is added by registering its handler.
+#[async_trait]
+pub trait EventHandler: Send + Sync + 'static {
+    /// Topic the handler consumes
+    const TOPIC: &'static str;
+    /// Names of the `oneof` cases the handler applies, each with its own metrics
+    const EVENTS: &'static [&'static str];
+
+    type Key: Message + Default + ToJson + Debug + Send;
+    type Event: Message + Default + ToJson + Debug + Send;
+
+    /// The name of the case of `event`, one of [`EVENTS`](Self::EVENTS), or `None` for events
+    /// without a projection
+    fn event(event: &Self::Event) -> Option<&'static str>;
+
+    /// The organization and project the rows written for `event` belong to
+    fn owner(key: &Self::Key, event: &Self::Event) -> Owner;
+
+    /// Applies `event`. Events without a projection are ignored.
+    ///
+    /// # Errors
+    /// Fails with [`MissingParent`] when a row the event refers to has not been recorded yet, or
+    /// with any other error if the projection cannot be written
+    async fn handle(&self, cx: &EventContext, key: Self::Key, event: Self::Event) -> Result<()>;
+}
+
+/// The organization and project the rows written by a message belong to, as given by the fields
+/// its handler reads them from
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Owner {
+    pub organization: Option<Uuid>,
+    pub project: Option<Uuid>,
+}
+
+impl Owner {
+    /// Rows of `project` in `organization`, either of which may be empty
+    #[must_use]
+    pub fn new(organization: &str, project: &str) -> Self {
+        Self {
+            organization: organization.parse().ok(),
+            project: project.parse().ok(),
+        }
+    }
+
+    /// Rows keyed by organization only
+    #[must_use]
+    pub fn organization(id: &str) -> Self {
+        Self::new(id, "")
+    }
+
+    /// Rows keyed by project only
+    #[must_use]
+    pub fn project(id: &str) -> Self {
+        Self::new("", id)
+    }
+}
+
+/// The connection and event time handlers write projections with
+#[derive(Debug, Clone)]
+pub struct EventContext {
+    pub db: Connection,
+    /// When the event happened, see [`Metadata`](crate::Metadata)
+    pub timestamp: DateTime,
+    /// Kafka offset of the message, telling apart changes recorded with the same timestamp
+    pub offset: i64,
+}
+
+impl EventContext {
+    /// Parses `id` and checks that the `E` row it refers to has been recorded.
+    ///
+    /// # Errors
+    /// Fails with [`MissingParent`] when the parent event has not been processed yet
+    async fn parent<E>(&self, id: &str) -> Result<Uuid>
+    where
+        E: EntityTrait,
+        Uuid: Into<<E::PrimaryKey as PrimaryKeyTrait>::ValueType>,
+    {
+        let id = parse_id(id)?;
+
+        if E::find_by_id(id).one(self.db.get()).await?.is_none() {
+            bail!(MissingParent {
+                table: E::default().table_name().to_string(),
+                id,
+            });
+        }
+
+        Ok(id)
+    }
+
+    /// Parses `id` and loads the `E` row it refers to, for projections that copy its columns.
+    ///
+    /// # Errors
+    /// Fails with [`MissingParent`] when the parent event has not been processed yet
+    async fn find<E>(&self, id: &str) -> Result<E::Model>
+    where
+        E: EntityTrait,
+        Uuid: Into<<E::PrimaryKey as PrimaryKeyTrait>::ValueType>,
+    {
+        let id = parse_id(id)?;
+
+        E::find_by_id(id).one(self.db.get()).await?.ok_or_else(|| {
+            MissingParent {
+                table: E::default().table_name().to_string(),
+                id,
+            }
+            .into()
+        })
+    }
+
+    /// Inserts `model`, treating a primary key conflict as a redelivery of the same event.
+    ///
+    /// Columns listed in `update` are mutable and take the incoming values on conflict; every
+    /// other column keeps what was recorded first. With no `update` columns the duplicate is
+    /// ignored.
+    async fn upsert<A>(&self, model: A, update: &[<A::Entity as EntityTrait>::Column]) -> Result<()>
+    where
+        A: ActiveModelTrait + Send,
+        <A::Entity as EntityTrait>::Model: IntoActiveModel<A>,
+    {
+        let mut on_conflict = OnConflict::columns(
+            <A::Entity as EntityTrait>::PrimaryKey::iter().map(PrimaryKeyToColumn::into_column),
+        );
+
+        if update.is_empty() {
+            on_conflict.do_nothing();
+        } else {
+            on_conflict.update_columns(update.iter().copied());
+        }
+
+        <A::Entity as EntityTrait>::insert(model)
+            .on_conflict(on_conflict)
+            .exec_without_returning(self.db.get())
+            .await?;
+
+        Ok(())
+    }
+
+    /// Records collection `id` of project `project_id` with the columns set in `collection`,
+    /// along with its first history entry. A redelivery only updates the name.
+    async fn create_collection(
+        &self,
+        id: &str,
+        project_id: &str,
+        collection: collections::ActiveModel,
+    ) -> Result<()> {
+        self.upsert(
+            collections::ActiveModel {
+                id: Set(parse_id(id)?),
+                project_id: Set(self.parent::<projects::Entity>(project_id).await?),
+                timestamp: Set(self.timestamp),
+                ..collection
+            },
+            &[collections::Column::Name],
+        )
+        .await?;
+
+        self.change_collection(id, CollectionChange::default())
+            .await
+    }
+
+    /// Records mint `id` of `collection_id` with its first `status`
+    async fn create_mint(
+        &self,
+        id: &str,
+        project_id: &str,
+        collection_id: &str,
+        status: &str,
+    ) -> Result<()> {
+        self.upsert(
+            mints::ActiveModel {
+                id: Set(parse_id(id)?),
+                collection_id: Set(self.parent::<collections::Entity>(collection_id).await?),
+                project_id: Set(self.parent::<projects::Entity>(project_id).await?),
+                status: Set(status.to_string()),
+                timestamp: Set(self.timestamp),
+            },
+            &[],
+        )
+        .await?;
+
+        self.set_mint_status(id, status.to_string()).await
+    }
+
+    /// Records transfer `id` of mint `mint_id` from `sender` to `recipient`
+    async fn record_transfer(
+        &self,
+        id: &str,
+        project_id: &str,
+        mint_id: &str,
+        sender: String,
+        recipient: String,
+    ) -> Result<()> {
+        let mint = self.find::<mints::Entity>(mint_id).await?;
+
+        self.upsert(
+            transfers::ActiveModel {
+                id: Set(parse_id(id)?),
+                project_id: Set(self.parent::<projects::Entity>(project_id).await?),
+                mint_id: Set(Some(mint.id)),
+                collection_id: Set(Some(mint.collection_id)),
+                sender: Set(Some(sender)),
+                recipient: Set(Some(recipient)),
+                timestamp: Set(self.timestamp),
+            },
+            &[],
+        )
+        .await
+    }
+
+    /// Records that mint `id` moved to `status` and makes it the mint's current status, unless a
+    /// later change was already recorded. Submissions and failures are published on the chain
+    /// topics while creation and retries come from `hub-nfts`, so they can be processed out of
+    /// order.
+    ///
+    /// # Errors
+    /// Fails with [`MissingParent`] when the mint has not been recorded yet
+    async fn set_mint_status(&self, id: &str, status: String) -> Result<()> {
+        let mint = self.find::<mints::Entity>(id).await?;
+        let id = mint.id;
+
+        self.upsert(
+            mint_status_changes::ActiveModel {
+                mint_id: Set(mint.id),
+                status: Set(status.clone()),
+                timestamp: Set(self.timestamp),
+                offset: Set(self.offset),
+                collection_id: Set(mint.collection_id),
+                project_id: Set(mint.project_id),
+            },
+            &[],
+        )
+        .await?;
+
+        let later = mint_status_changes::Entity::find()
+            .select_only()
+            .column(mint_status_changes::Column::MintId)
+            .filter(mint_status_changes::Column::MintId.eq(id))
+            .filter(
+                Condition::any()
+                    .add(mint_status_changes::Column::Timestamp.gt(self.timestamp))
+                    .add(
+                        Condition::all()
+                            .add(mint_status_changes::Column::Timestamp.eq(self.timestamp))
+                            .add(mint_status_changes::Column::Offset.gt(self.offset)),
+                    ),
+            )
+            .into_query();
+
+        mints::Entity::update_many()
+            .col_expr(mints::Column::Status, Expr::value(status))
+            .filter(mints::Column::Id.eq(id))
+            .filter(mints::Column::Id.not_in_subquery(later))
+            .exec(self.db.get())
+            .await?;
+
+        Ok(())
+    }
+
+    /// Records the state of collection `id` after `change` in its history and applies it to the
+    /// collection, unless a later change was already recorded.
+    ///
+    /// # Errors
+    /// Fails with [`MissingParent`] when the collection has not been recorded yet
+    async fn change_collection(&self, id: &str, change: CollectionChange) -> Result<()> {
+        let collection = self.find::<collections::Entity>(id).await?;
+        let id = collection.id;
+        let status = change.status.map_or(collection.status, ToString::to_string);
+        let name = change.name.unwrap_or(collection.name);
+        let supply = change.supply.unwrap_or(collection.supply);
+
+        self.upsert(
+            collection_history::ActiveModel {
+                collection_id: Set(id),
+                timestamp: Set(self.timestamp),
+                offset: Set(self.offset),
+                project_id: Set(collection.project_id),
+                status: Set(status.clone()),
+                name: Set(name.clone()),
+                supply: Set(supply),
+            },
+            &[],
+        )
+        .await?;
+
+        let later = collection_history::Entity::find()
+            .select_only()
+            .column(collection_history::Column::CollectionId)
+            .filter(collection_history::Column::CollectionId.eq(id))
+            .filter(
+                Condition::any()
+                    .add(collection_history::Column::Timestamp.gt(self.timestamp))
+                    .add(
+                        Condition::all()
+                            .add(collection_history::Column::Timestamp.eq(self.timestamp))
+                            .add(collection_history::Column::Offset.gt(self.offset)),
+                    ),
+            )
+            .into_query();
+
+        collections::Entity::update_many()
+            .col_expr(collections::Column::Status, Expr::value(status))
+            .col_expr(collections::Column::Name, Expr::value(name))
+            .col_expr(collections::Column::Supply, Expr::value(supply))
+            .filter(collections::Column::Id.eq(id))
+            .filter(collections::Column::Id.not_in_subquery(later))
+            .exec(self.db.get())
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Fields of a collection changed by a drop event. Fields left as `None` keep their value, while
+/// `supply: Some(None)` makes the collection an open edition.
+#[derive(Default)]
+#[allow(clippy::option_option)]
+struct CollectionChange {
+    status: Option<&'static str>,
+    name: Option<String>,
+    supply: Option<Option<i64>>,
+}
+
+impl CollectionChange {
+    fn status(status: &'static str) -> Self {
+        Self {
+            status: Some(status),
+            ..Self::default()
+        }
+    }
+}
+
+/// Parses an id carried by an event
+///
+/// # Errors
+/// This function fails if `id` is not a UUID
+fn parse_id(id: &str) -> Result<Uuid> {
+    Uuid::parse_str(id).with_context(|| format!("invalid id {id:?}"))
+}
+
+fn int_to_blockchain(n: i32) -> String {
+    match n {
+        1 => "Solana",
+        2 => "Polygon",
+        3 => "Ethereum",
+        _ => "Unspecified",
+    }
+    .to_string()
+}
+
+fn int_to_action(n: i32) -> String {
+    Action::from_i32(n)
+        .map_or("UNSPECIFIED", |a| a.as_str_name())
+        .to_string()
+}
+
+fn int_to_creation_status(n: i32) -> String {
+    CreationStatus::from_i32(n)
+        .map_or("UNSPECIFIED", |s| s.as_str_name())
+        .to_string()
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use hub_core::{chrono::NaiveDate, uuid::Uuid};
+    use prost::Message;
+    use sea_orm::{DatabaseBackend, MockDatabase, Transaction};
+
+    use super::EventContext;
+    use crate::{
+        entities::{collections, mints, organizations, projects},
+        proto::{credits_event, CreditPurchase, CreditsEvent, CreditsEventKey},
+        Envelope, Metadata,
+    };
+
+    pub const ORGANIZATION: &str = "1d4c2a33-7a7e-4b8c-9c0e-6e7b0a2d8f10";
+    pub const PROJECT: &str = "2f6b5c44-8b8f-4c9d-8d1f-7f8c1b3e9a21";
+    pub const COLLECTION: &str = "3a7c6d55-9c90-4dae-9e20-809d2c4fab32";
+    pub const MINT: &str = "4b8d7e66-ad01-4ebf-af31-91ae3d50bc43";
+
+    pub fn timestamp() -> super::DateTime {
+        NaiveDate::from_ymd_opt(2023, 9, 1)
+            .and_then(|d| d.and_hms_opt(12, 0, 0))
+            .unwrap()
+    }
+
+    pub fn mock() -> MockDatabase {
+        MockDatabase::new(DatabaseBackend::Postgres)
+    }
+
+    pub fn context(db: MockDatabase) -> EventContext {
+        EventContext {
+            db: db.into_connection().into(),
+            timestamp: timestamp(),
+            offset: 0,
+        }
+    }
+
+    /// The statements run by the handlers called with `cx`
+    pub fn log(cx: EventContext) -> Vec<Transaction> {
+        cx.db.into_inner().unwrap().into_transaction_log()
+    }
+
+    pub fn statement<const N: usize>(sql: &str, values: [sea_orm::Value; N]) -> Transaction {
+        Transaction::from_sql_and_values(DatabaseBackend::Postgres, sql, values)
+    }
+
+    /// The lookup of project [`PROJECT`] by [`EventContext::parent`]
+    pub fn find_project() -> Transaction {
+        statement(
+            r#"SELECT "projects"."id", "projects"."name", "projects"."organization_id", "projects"."timestamp", "projects"."deactivated_at" FROM "projects" WHERE "projects"."id" = $1 LIMIT $2"#,
+            [id(PROJECT), 1u64.into()],
+        )
+    }
+
+    pub fn id(id: &str) -> sea_orm::Value {
+        id.parse::<Uuid>().unwrap().into()
+    }
+
+    /// A purchase of `credit` by [`ORGANIZATION`], keyed by the credit and received at `offset`
+    /// of `hub-credits` partition 0
+    pub fn purchase(credit: &str, offset: i64) -> Envelope {
+        let key = CreditsEventKey {
+            id: credit.to_string(),
+            ..CreditsEventKey::default()
+        };
+        let event = credits_event::Event::CreditsPurchased(CreditPurchase {
+            organization_id: ORGANIZATION.to_string(),
+            amount: 100,
+        });
+
+        Envelope {
+            metadata: Metadata {
+                topic: "hub-credits".to_string(),
+                partition: 0,
+                offset,
+                timestamp: timestamp(),
+            },
+            key: key.encode_to_vec(),
+            payload: CreditsEvent { event: Some(event) }.encode_to_vec(),
+        }
+    }
+
+    pub fn organization() -> organizations::Model {
+        organizations::Model {
+            id: ORGANIZATION.parse().unwrap(),
+            name: "Holaplex".to_string(),
+        }
+    }
+
+    /// Whether a statement in `log` contains `sql`, or was run with a value printed as `sql`
+    pub fn ran(log: &[Transaction], sql: &str) -> bool {
+        let sql = sql.replace('"', "\\\"");
+
+        log.iter().any(|t| format!("{t:?}").contains(&sql))
+    }
+
+    pub fn project() -> projects::Model {
+        projects::Model {
+            id: PROJECT.parse().unwrap(),
+            name: "Drops".to_string(),
+            organization_id: ORGANIZATION.parse().unwrap(),
+            timestamp: timestamp(),
+            deactivated_at: None,
+        }
+    }
+
+    pub fn collection() -> collections::Model {
+        collections::Model {
+            id: COLLECTION.parse().unwrap(),
+            name: "Launch".to_string(),
+            project_id: PROJECT.parse().unwrap(),
+            blockchain: "Solana".to_string(),
+            timestamp: timestamp(),
+            status: "ACTIVE".to_string(),
+            supply: Some(100),
+            seller_fee_basis_points: Some(500),
+            creator: None,
+        }
+    }
+
+    pub fn mint() -> mints::Model {
+        mints::Model {
+            id: MINT.parse().unwrap(),
+            project_id: PROJECT.parse().unwrap(),
+            collection_id: COLLECTION.parse().unwrap(),
+            timestamp: timestamp(),
+            status: "PENDING".to_string(),
+        }
+    }
+}
@@ -0,0 +1,286 @@
+//! Drops, their mints and transfers
+
+use hub_core::prelude::*;
+use poem::async_trait;
+use sea_orm::Set;
+
+use super::{int_to_creation_status, CollectionChange, EventContext, EventHandler, Owner};
+use crate::{
+    entities::collections,
+    journal::{messages, oneofs},
+    proto::{
+        nft_events::Event, CreateEditionTransaction, DropLifecycle,
+        MetaplexMasterEditionTransaction, MintCreation, MintEditionTransaction,
+        MintMetaplexEditionTransaction, MintTransfer, NftEventKey as Key, NftEvents,
+        UpdateEditionTransaction,
+    },
+};
+
+/// Drops, their mints and transfers
+pub struct Nfts;
+
+#[async_trait]
+impl EventHandler for Nfts {
+    const TOPIC: &'static str = "hub-nfts";
+    const EVENTS: &'static [&'static str] = &[
+        "SolanaCreateDrop",
+        "PolygonCreateDrop",
+        "SolanaUpdateDrop",
+        "PolygonUpdateDrop",
+        "PauseDrop",
+        "ResumeDrop",
+        "ShutdownDrop",
+        "SolanaMintDrop",
+        "PolygonMintDrop",
+        "TransferMint",
+        "DropMinted",
+        "SolanaRetryMintDrop",
+        "PolygonRetryMintDrop",
+    ];
+
+    type Key = Key;
+    type Event = NftEvents;
+
+    fn event(v: &Self::Event) -> Option<&'static str> {
+        match &v.event {
+            Some(Event::SolanaCreateDrop(_)) => Some("SolanaCreateDrop"),
+            Some(Event::PolygonCreateDrop(_)) => Some("PolygonCreateDrop"),
+            Some(Event::SolanaUpdateDrop(_)) => Some("SolanaUpdateDrop"),
+            Some(Event::PolygonUpdateDrop(_)) => Some("PolygonUpdateDrop"),
+            Some(Event::PauseDrop(_)) => Some("PauseDrop"),
+            Some(Event::ResumeDrop(_)) => Some("ResumeDrop"),
+            Some(Event::ShutdownDrop(_)) => Some("ShutdownDrop"),
+            Some(Event::SolanaMintDrop(_)) => Some("SolanaMintDrop"),
+            Some(Event::PolygonMintDrop(_)) => Some("PolygonMintDrop"),
+            Some(Event::TransferMint(_)) => Some("TransferMint"),
+            Some(Event::DropMinted(_)) => Some("DropMinted"),
+            Some(Event::SolanaRetryMintDrop(_)) => Some("SolanaRetryMintDrop"),
+            Some(Event::PolygonRetryMintDrop(_)) => Some("PolygonRetryMintDrop"),
+            _ => None,
+        }
+    }
+
+    fn owner(k: &Key, _: &Self::Event) -> Owner {
+        Owner::project(&k.project_id)
+    }
+
+    async fn handle(&self, cx: &EventContext, k: Key, v: Self::Event) -> Result<()> {
+        match v.event {
+            Some(Event::SolanaCreateDrop(v)) => solana_create_drop(cx, k, v).await,
+            Some(Event::PolygonCreateDrop(v)) => polygon_create_drop(cx, k, v).await,
+            Some(Event::SolanaUpdateDrop(v)) => solana_update_drop(cx, k, v).await,
+            Some(Event::PolygonUpdateDrop(v)) => polygon_update_drop(cx, k, v).await,
+            Some(Event::PauseDrop(v)) => pause_drop(cx, k, v).await,
+            Some(Event::ResumeDrop(v)) => resume_drop(cx, k, v).await,
+            Some(Event::ShutdownDrop(v)) => shutdown_drop(cx, k, v).await,
+            Some(Event::SolanaMintDrop(v)) => solana_mint_drop(cx, k, v).await,
+            Some(Event::PolygonMintDrop(v)) => polygon_mint_drop(cx, k, v).await,
+            Some(Event::TransferMint(v)) => transfer_mint(cx, k, v).await,
+            Some(Event::DropMinted(v)) => drop_minted(cx, k, v).await,
+            Some(Event::SolanaRetryMintDrop(v)) => retry_mint_drop(cx, k, v).await,
+            Some(Event::PolygonRetryMintDrop(v)) => retry_mint_drop(cx, k, v).await,
+            _ => Ok(()),
+        }
+    }
+}
+
+messages! {
+    NftEventKey { id, user_id, project_id },
+    NftEvents { event },
+    Creator { address, verified, share },
+    MasterEdition {
+        name,
+        symbol,
+        metadata_uri,
+        seller_fee_basis_points,
+        supply,
+        creators,
+        owner_address,
+    },
+    MetaplexMasterEditionTransaction { master_edition },
+    EditionInfo { description, image_uri, collection, uri, creator },
+    CreateEditionTransaction { amount, edition_info, fee_receiver, fee_numerator, receiver },
+    UpdateEditionTransaction { edition_info },
+    MintMetaplexEditionTransaction { recipient_address, owner_address, edition, collection_id },
+    MintEditionTransaction { receiver, amount, collection_id },
+    MintTransfer { mint_id, sender, recipient },
+    MintCreation { drop_id, status },
+    DropLifecycle {},
+}
+
+oneofs! {
+    nft_events { SolanaCreateDrop, PolygonCreateDrop, SolanaUpdateDrop, PolygonUpdateDrop, PauseDrop, ResumeDrop, ShutdownDrop, SolanaMintDrop, PolygonMintDrop, TransferMint, DropMinted, SolanaRetryMintDrop, PolygonRetryMintDrop },
+}
+
+async fn solana_create_drop(
+    cx: &EventContext,
+    k: Key,
+    v: MetaplexMasterEditionTransaction,
+) -> Result<()> {
+    let master_edition = v.master_edition.unwrap_or_default();
+    let collection = collections::ActiveModel {
+        name: Set(master_edition.name),
+        blockchain: Set("Solana".to_string()),
+        status: Set("ACTIVE".to_string()),
+        supply: Set(master_edition.supply.map(i64::try_from).transpose()?),
+        seller_fee_basis_points: Set(Some(master_edition.seller_fee_basis_points.try_into()?)),
+        creator: Set(master_edition
+            .creators
+            .into_iter()
+            .next()
+            .map(|c| c.address)),
+        ..Default::default()
+    };
+
+    cx.create_collection(&k.id, &k.project_id, collection).await
+}
+
+async fn polygon_create_drop(cx: &EventContext, k: Key, v: CreateEditionTransaction) -> Result<()> {
+    let edition_info = v.edition_info.unwrap_or_default();
+    let collection = collections::ActiveModel {
+        name: Set(edition_info.collection),
+        blockchain: Set("Polygon".to_string()),
+        status: Set("ACTIVE".to_string()),
+        supply: Set(Some(v.amount)),
+        seller_fee_basis_points: Set(Some(v.fee_numerator.try_into()?)),
+        creator: Set(Some(edition_info.creator)),
+        ..Default::default()
+    };
+
+    cx.create_collection(&k.id, &k.project_id, collection).await
+}
+
+async fn solana_update_drop(
+    cx: &EventContext,
+    k: Key,
+    v: MetaplexMasterEditionTransaction,
+) -> Result<()> {
+    let master_edition = v.master_edition.unwrap_or_default();
+    let change = CollectionChange {
+        name: Some(master_edition.name),
+        supply: Some(master_edition.supply.map(i64::try_from).transpose()?),
+        ..CollectionChange::default()
+    };
+
+    cx.change_collection(&k.id, change).await
+}
+
+async fn polygon_update_drop(cx: &EventContext, k: Key, v: UpdateEditionTransaction) -> Result<()> {
+    let change = CollectionChange {
+        name: Some(v.edition_info.unwrap_or_default().collection),
+        ..CollectionChange::default()
+    };
+
+    cx.change_collection(&k.id, change).await
+}
+
+async fn pause_drop(cx: &EventContext, k: Key, _: DropLifecycle) -> Result<()> {
+    cx.change_collection(&k.id, CollectionChange::status("PAUSED"))
+        .await
+}
+
+async fn resume_drop(cx: &EventContext, k: Key, _: DropLifecycle) -> Result<()> {
+    cx.change_collection(&k.id, CollectionChange::status("ACTIVE"))
+        .await
+}
+
+async fn shutdown_drop(cx: &EventContext, k: Key, _: DropLifecycle) -> Result<()> {
+    cx.change_collection(&k.id, CollectionChange::status("SHUT_DOWN"))
+        .await
+}
+
+async fn solana_mint_drop(
+    cx: &EventContext,
+    k: Key,
+    v: MintMetaplexEditionTransaction,
+) -> Result<()> {
+    cx.create_mint(&k.id, &k.project_id, &v.collection_id, "PENDING")
+        .await
+}
+
+async fn polygon_mint_drop(cx: &EventContext, k: Key, v: MintEditionTransaction) -> Result<()> {
+    cx.create_mint(&k.id, &k.project_id, &v.collection_id, "PENDING")
+        .await
+}
+
+async fn transfer_mint(cx: &EventContext, k: Key, v: MintTransfer) -> Result<()> {
+    cx.record_transfer(&k.id, &k.project_id, &v.mint_id, v.sender, v.recipient)
+        .await
+}
+
+async fn drop_minted(cx: &EventContext, k: Key, v: MintCreation) -> Result<()> {
+    cx.set_mint_status(&k.id, int_to_creation_status(v.status))
+        .await
+}
+
+/// A Solana or Polygon mint is retried
+async fn retry_mint_drop<T>(cx: &EventContext, k: Key, _: T) -> Result<()> {
+    cx.set_mint_status(&k.id, "RETRYING".to_string()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use hub_core::tokio;
+    use sea_orm::MockExecResult;
+
+    use super::{solana_update_drop, Key};
+    use crate::{
+        events::test::*,
+        proto::{MasterEdition, MetaplexMasterEditionTransaction},
+    };
+
+    #[tokio::test(crate = "hub_core::tokio")]
+    async fn update_to_open_edition_clears_supply() {
+        let cx = context(
+            mock()
+                .append_query_results([[collection()]])
+                .append_exec_results([MockExecResult::default(), MockExecResult::default()]),
+        );
+        let key = Key {
+            id: COLLECTION.to_string(),
+            project_id: PROJECT.to_string(),
+            ..Key::default()
+        };
+        let update = MetaplexMasterEditionTransaction {
+            master_edition: Some(MasterEdition {
+                name: "Launch".to_string(),
+                supply: None,
+                ..MasterEdition::default()
+            }),
+        };
+
+        solana_update_drop(&cx, key, update).await.unwrap();
+
+        assert_eq!(log(cx), [
+            statement(
+                r#"SELECT "collections"."id", "collections"."name", "collections"."project_id", "collections"."blockchain", "collections"."timestamp", "collections"."status", "collections"."supply", "collections"."seller_fee_basis_points", "collections"."creator" FROM "collections" WHERE "collections"."id" = $1 LIMIT $2"#,
+                [id(COLLECTION), 1u64.into()],
+            ),
+            statement(
+                r#"INSERT INTO "collection_history" ("collection_id", "timestamp", "offset", "project_id", "status", "name", "supply") VALUES ($1, $2, $3, $4, $5, $6, $7) ON CONFLICT ("collection_id", "timestamp", "offset") DO NOTHING"#,
+                [
+                    id(COLLECTION),
+                    timestamp().into(),
+                    0i64.into(),
+                    id(PROJECT),
+                    "ACTIVE".into(),
+                    "Launch".into(),
+                    None::<i64>.into(),
+                ],
+            ),
+            statement(
+                r#"UPDATE "collections" SET "status" = $1, "name" = $2, "supply" = $3 WHERE "collections"."id" = $4 AND "collections"."id" NOT IN (SELECT "collection_history"."collection_id" FROM "collection_history" WHERE "collection_history"."collection_id" = $5 AND ("collection_history"."timestamp" > $6 OR ("collection_history"."timestamp" = $7 AND "collection_history"."offset" > $8)))"#,
+                [
+                    "ACTIVE".into(),
+                    "Launch".into(),
+                    None::<i64>.into(),
+                    id(COLLECTION),
+                    id(COLLECTION),
+                    timestamp().into(),
+                    timestamp().into(),
+                    0i64.into(),
+                ],
+            ),
+        ]);
+    }
+}
@@ -0,0 +1,368 @@
+//! Organizations, their projects and members
+
+use hub_core::prelude::*;
+use poem::async_trait;
+use sea_orm::{prelude::*, sea_query::Expr, Condition, QuerySelect, QueryTrait, Set};
+
+use super::{parse_id, EventContext, EventHandler, Owner};
+use crate::{
+    entities::{invites, member_status_changes, members, organizations, projects},
+    journal::{messages, oneofs},
+    proto::{
+        organization_events::Event, Invite, Member, Organization, OrganizationEventKey as Key,
+        OrganizationEvents, Project,
+    },
+};
+
+/// Organizations, their projects and members
+pub struct Organizations;
+
+#[async_trait]
+impl EventHandler for Organizations {
+    const TOPIC: &'static str = "hub-orgs";
+    const EVENTS: &'static [&'static str] = &[
+        "OrganizationCreated",
+        "ProjectCreated",
+        "ProjectDeactivated",
+        "InviteCreated",
+        "InviteAccepted",
+        "MemberAdded",
+        "MemberDeactivated",
+        "MemberReactivated",
+    ];
+
+    type Key = Key;
+    type Event = OrganizationEvents;
+
+    fn event(v: &Self::Event) -> Option<&'static str> {
+        match &v.event {
+            Some(Event::OrganizationCreated(_)) => Some("OrganizationCreated"),
+            Some(Event::ProjectCreated(_)) => Some("ProjectCreated"),
+            Some(Event::ProjectDeactivated(_)) => Some("ProjectDeactivated"),
+            Some(Event::InviteCreated(_)) => Some("InviteCreated"),
+            Some(Event::InviteAccepted(_)) => Some("InviteAccepted"),
+            Some(Event::MemberAdded(_)) => Some("MemberAdded"),
+            Some(Event::MemberDeactivated(_)) => Some("MemberDeactivated"),
+            Some(Event::MemberReactivated(_)) => Some("MemberReactivated"),
+            _ => None,
+        }
+    }
+
+    fn owner(k: &Key, v: &Self::Event) -> Owner {
+        match &v.event {
+            Some(Event::OrganizationCreated(_)) => Owner::organization(&k.id),
+            Some(Event::ProjectCreated(p) | Event::ProjectDeactivated(p)) => {
+                Owner::new(&p.organization_id, &k.id)
+            },
+            Some(Event::InviteCreated(i)) => Owner::organization(&i.organization_id),
+            Some(
+                Event::InviteAccepted(m)
+                | Event::MemberAdded(m)
+                | Event::MemberDeactivated(m)
+                | Event::MemberReactivated(m),
+            ) => Owner::organization(&m.organization_id),
+            _ => Owner::default(),
+        }
+    }
+
+    async fn handle(&self, cx: &EventContext, k: Key, v: Self::Event) -> Result<()> {
+        match v.event {
+            Some(Event::OrganizationCreated(v)) => organization_created(cx, k, v).await,
+            Some(Event::ProjectCreated(v)) => project_created(cx, k, v).await,
+            Some(Event::ProjectDeactivated(v)) => project_deactivated(cx, k, v).await,
+            Some(Event::InviteCreated(v)) => invite_created(cx, k, v).await,
+            Some(Event::InviteAccepted(v) | Event::MemberAdded(v)) => member_added(cx, k, v).await,
+            Some(Event::MemberDeactivated(v)) => member_deactivated(cx, k, v).await,
+            Some(Event::MemberReactivated(v)) => member_reactivated(cx, k, v).await,
+            _ => Ok(()),
+        }
+    }
+}
+
+messages! {
+    OrganizationEventKey { id, user_id },
+    OrganizationEvents { event },
+    Organization { name },
+    Project { name, organization_id },
+    Invite { organization_id, email },
+    Member { organization_id, user_id },
+}
+
+oneofs! {
+    organization_events { OrganizationCreated, ProjectCreated, ProjectDeactivated, InviteCreated, InviteAccepted, MemberAdded, MemberDeactivated, MemberReactivated },
+}
+
+async fn organization_created(cx: &EventContext, k: Key, v: Organization) -> Result<()> {
+    cx.upsert(
+        organizations::ActiveModel {
+            id: Set(parse_id(&k.id)?),
+            name: Set(v.name),
+        },
+        &[organizations::Column::Name],
+    )
+    .await
+}
+
+async fn project_created(cx: &EventContext, k: Key, v: Project) -> Result<()> {
+    cx.upsert(
+        projects::ActiveModel {
+            id: Set(parse_id(&k.id)?),
+            name: Set(v.name),
+            organization_id: Set(cx
+                .parent::<organizations::Entity>(&v.organization_id)
+                .await?),
+            timestamp: Set(cx.timestamp),
+            deactivated_at: Set(None),
+        },
+        &[projects::Column::Name],
+    )
+    .await
+}
+
+/// Only sets the deactivation time, a project deactivated before its creation was processed
+/// waits for it rather than being recorded as created at the time of deactivation
+async fn project_deactivated(cx: &EventContext, k: Key, _: Project) -> Result<()> {
+    let project = cx.find::<projects::Entity>(&k.id).await?;
+
+    projects::Entity::update_many()
+        .col_expr(
+            projects::Column::DeactivatedAt,
+            Expr::value(Some(cx.timestamp)),
+        )
+        .filter(projects::Column::Id.eq(project.id))
+        .exec(cx.db.get())
+        .await?;
+
+    Ok(())
+}
+
+async fn invite_created(cx: &EventContext, k: Key, v: Invite) -> Result<()> {
+    cx.upsert(
+        invites::ActiveModel {
+            id: Set(parse_id(&k.id)?),
+            organization_id: Set(cx
+                .parent::<organizations::Entity>(&v.organization_id)
+                .await?),
+            timestamp: Set(cx.timestamp),
+        },
+        &[],
+    )
+    .await
+}
+
+/// An invite was accepted or a member was added directly. The member is keyed by its own id
+/// rather than the invite's, so pending invites are kept apart in `invites`.
+async fn member_added(cx: &EventContext, k: Key, v: Member) -> Result<()> {
+    cx.upsert(
+        members::ActiveModel {
+            id: Set(parse_id(&k.id)?),
+            organization_id: Set(cx
+                .parent::<organizations::Entity>(&v.organization_id)
+                .await?),
+            status: Set("ACTIVE".to_string()),
+            timestamp: Set(cx.timestamp),
+            joined_at: Set(Some(cx.timestamp)),
+            deactivated_at: Set(None),
+        },
+        &[],
+    )
+    .await?;
+
+    set_member_status(cx, &k.id, "ACTIVE", None).await
+}
+
+async fn member_deactivated(cx: &EventContext, k: Key, _: Member) -> Result<()> {
+    set_member_status(cx, &k.id, "DEACTIVATED", Some(cx.timestamp)).await
+}
+
+async fn member_reactivated(cx: &EventContext, k: Key, _: Member) -> Result<()> {
+    set_member_status(cx, &k.id, "ACTIVE", None).await
+}
+
+/// Records that member `id` moved to `status` and makes it the member's current status, unless a
+/// later change was already recorded.
+///
+/// # Errors
+/// Fails with [`MissingParent`](super::MissingParent) when the member has not been recorded yet
+async fn set_member_status(
+    cx: &EventContext,
+    id: &str,
+    status: &str,
+    deactivated_at: Option<DateTime>,
+) -> Result<()> {
+    let member = cx.find::<members::Entity>(id).await?;
+    let id = member.id;
+
+    cx.upsert(
+        member_status_changes::ActiveModel {
+            member_id: Set(id),
+            timestamp: Set(cx.timestamp),
+            offset: Set(cx.offset),
+            status: Set(status.to_string()),
+            organization_id: Set(member.organization_id),
+        },
+        &[],
+    )
+    .await?;
+
+    let later = member_status_changes::Entity::find()
+        .select_only()
+        .column(member_status_changes::Column::MemberId)
+        .filter(member_status_changes::Column::MemberId.eq(id))
+        .filter(
+            Condition::any()
+                .add(member_status_changes::Column::Timestamp.gt(cx.timestamp))
+                .add(
+                    Condition::all()
+                        .add(member_status_changes::Column::Timestamp.eq(cx.timestamp))
+                        .add(member_status_changes::Column::Offset.gt(cx.offset)),
+                ),
+        )
+        .into_query();
+
+    members::Entity::update_many()
+        .col_expr(members::Column::Status, Expr::value(status))
+        .col_expr(members::Column::DeactivatedAt, Expr::value(deactivated_at))
+        .filter(members::Column::Id.eq(id))
+        .filter(members::Column::Id.not_in_subquery(later))
+        .exec(cx.db.get())
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use hub_core::tokio;
+    use sea_orm::MockExecResult;
+
+    use super::{
+        invite_created, member_deactivated, project_deactivated, Invite, Key, Member, Project,
+    };
+    use crate::{
+        entities::{members, organizations, projects},
+        events::{test::*, MissingParent},
+    };
+
+    const MEMBER: &str = "6d0fa188-cf23-4e1d-a153-b3c05f72de65";
+
+    fn key() -> Key {
+        Key {
+            id: MEMBER.to_string(),
+            ..Key::default()
+        }
+    }
+
+    fn member() -> Member {
+        Member {
+            organization_id: ORGANIZATION.to_string(),
+            ..Member::default()
+        }
+    }
+
+    #[tokio::test(crate = "hub_core::tokio")]
+    async fn invite_is_not_recorded_as_member() {
+        let cx = context(
+            mock()
+                .append_query_results([[organizations::Model {
+                    id: ORGANIZATION.parse().unwrap(),
+                    name: "Holaplex".to_string(),
+                }]])
+                .append_exec_results([MockExecResult::default()]),
+        );
+        let invite = Invite {
+            organization_id: ORGANIZATION.to_string(),
+            ..Invite::default()
+        };
+
+        invite_created(&cx, key(), invite).await.unwrap();
+
+        assert_eq!(log(cx), [
+            statement(
+                r#"SELECT "organizations"."id", "organizations"."name" FROM "organizations" WHERE "organizations"."id" = $1 LIMIT $2"#,
+                [id(ORGANIZATION), 1u64.into()],
+            ),
+            statement(
+                r#"INSERT INTO "invites" ("id", "organization_id", "timestamp") VALUES ($1, $2, $3) ON CONFLICT ("id") DO NOTHING"#,
+                [id(MEMBER), id(ORGANIZATION), timestamp().into()],
+            ),
+        ]);
+    }
+
+    #[tokio::test(crate = "hub_core::tokio")]
+    async fn deactivation_is_not_applied_over_later_changes() {
+        let cx = context(
+            mock()
+                .append_query_results([[members::Model {
+                    id: MEMBER.parse().unwrap(),
+                    organization_id: ORGANIZATION.parse().unwrap(),
+                    status: "ACTIVE".to_string(),
+                    timestamp: timestamp(),
+                    joined_at: Some(timestamp()),
+                    deactivated_at: None,
+                }]])
+                .append_exec_results([MockExecResult::default(), MockExecResult::default()]),
+        );
+
+        member_deactivated(&cx, key(), member()).await.unwrap();
+
+        assert_eq!(log(cx), [
+            statement(
+                r#"SELECT "members"."id", "members"."organization_id", "members"."status", "members"."timestamp", "members"."joined_at", "members"."deactivated_at" FROM "members" WHERE "members"."id" = $1 LIMIT $2"#,
+                [id(MEMBER), 1u64.into()],
+            ),
+            statement(
+                r#"INSERT INTO "member_status_changes" ("member_id", "timestamp", "offset", "status", "organization_id") VALUES ($1, $2, $3, $4, $5) ON CONFLICT ("member_id", "timestamp", "offset") DO NOTHING"#,
+                [
+                    id(MEMBER),
+                    timestamp().into(),
+                    0i64.into(),
+                    "DEACTIVATED".into(),
+                    id(ORGANIZATION),
+                ],
+            ),
+            statement(
+                r#"UPDATE "members" SET "status" = $1, "deactivated_at" = $2 WHERE "members"."id" = $3 AND "members"."id" NOT IN (SELECT "member_status_changes"."member_id" FROM "member_status_changes" WHERE "member_status_changes"."member_id" = $4 AND ("member_status_changes"."timestamp" > $5 OR ("member_status_changes"."timestamp" = $6 AND "member_status_changes"."offset" > $7)))"#,
+                [
+                    "DEACTIVATED".into(),
+                    Some(timestamp()).into(),
+                    id(MEMBER),
+                    id(MEMBER),
+                    timestamp().into(),
+                    timestamp().into(),
+                    0i64.into(),
+                ],
+            ),
+        ]);
+    }
+
+    #[tokio::test(crate = "hub_core::tokio")]
+    async fn deactivation_before_member_is_missing_parent() {
+        let cx = context(mock().append_query_results([Vec::<members::Model>::new()]));
+
+        let err = member_deactivated(&cx, key(), member()).await.unwrap_err();
+        let missing = err.downcast_ref::<MissingParent>().unwrap();
+
+        assert_eq!(missing.table, "members");
+        assert_eq!(missing.id.to_string(), MEMBER);
+        assert_eq!(log(cx).len(), 1);
+    }
+
+    #[tokio::test(crate = "hub_core::tokio")]
+    async fn project_deactivated_before_created_is_missing_parent() {
+        let cx = context(mock().append_query_results([Vec::<projects::Model>::new()]));
+        let key = Key {
+            id: PROJECT.to_string(),
+            ..Key::default()
+        };
+
+        let err = project_deactivated(&cx, key, Project::default())
+            .await
+            .unwrap_err();
+        let missing = err.downcast_ref::<MissingParent>().unwrap();
+
+        assert_eq!(missing.table, "projects");
+        assert_eq!(missing.id.to_string(), PROJECT);
+        assert_eq!(log(cx), [find_project()]);
+    }
+}
@@ -0,0 +1,130 @@
+//! Imports, mint and transfer transactions on Polygon
+
+use hub_core::prelude::*;
+use poem::async_trait;
+use sea_orm::Set;
+
+use super::{CollectionChange, EventContext, EventHandler, Owner};
+use crate::{
+    entities::collections,
+    journal::{messages, oneofs},
+    proto::{
+        polygon_nft_events::Event, PolygonCollection, PolygonCollectionMint,
+        PolygonNftEventKey as Key, PolygonNftEvents, PolygonTransaction, PolygonTransactionFailure,
+        PolygonTransferAsset,
+    },
+};
+
+/// Imports, mint and transfer transactions on Polygon
+pub struct PolygonNfts;
+
+#[async_trait]
+impl EventHandler for PolygonNfts {
+    const TOPIC: &'static str = "hub-nfts-polygon";
+    const EVENTS: &'static [&'static str] = &[
+        "ImportedExternalCollection",
+        "ImportedExternalMint",
+        "TransferAssetSubmitted",
+        "CreateDropSubmitted",
+        "CreateDropFailed",
+        "MintDropSubmitted",
+        "MintDropFailed",
+    ];
+
+    type Key = Key;
+    type Event = PolygonNftEvents;
+
+    fn event(v: &Self::Event) -> Option<&'static str> {
+        match &v.event {
+            Some(Event::ImportedExternalCollection(_)) => Some("ImportedExternalCollection"),
+            Some(Event::ImportedExternalMint(_)) => Some("ImportedExternalMint"),
+            Some(Event::TransferAssetSubmitted(_)) => Some("TransferAssetSubmitted"),
+            Some(Event::CreateDropSubmitted(_)) => Some("CreateDropSubmitted"),
+            Some(Event::CreateDropFailed(_)) => Some("CreateDropFailed"),
+            Some(Event::MintDropSubmitted(_)) => Some("MintDropSubmitted"),
+            Some(Event::MintDropFailed(_)) => Some("MintDropFailed"),
+            _ => None,
+        }
+    }
+
+    fn owner(k: &Key, _: &Self::Event) -> Owner {
+        Owner::project(&k.project_id)
+    }
+
+    async fn handle(&self, cx: &EventContext, k: Key, v: Self::Event) -> Result<()> {
+        match v.event {
+            Some(Event::ImportedExternalCollection(v)) => imported_collection(cx, k, v).await,
+            Some(Event::ImportedExternalMint(v)) => imported_mint(cx, k, v).await,
+            Some(Event::TransferAssetSubmitted(v)) => transfer_submitted(cx, k, v).await,
+            Some(Event::CreateDropSubmitted(v)) => create_drop_submitted(cx, k, v).await,
+            Some(Event::CreateDropFailed(v)) => create_drop_failed(cx, k, v).await,
+            Some(Event::MintDropSubmitted(v)) => mint_submitted(cx, k, v).await,
+            Some(Event::MintDropFailed(v)) => mint_failed(cx, k, v).await,
+            _ => Ok(()),
+        }
+    }
+}
+
+messages! {
+    PolygonNftEventKey { id, user_id, project_id },
+    PolygonNftEvents { event },
+    PolygonCollection { name, address },
+    PolygonCollectionMint { collection_id, owner },
+    PolygonTransferAsset { collection_mint_id, owner_address, recipient_address },
+    PolygonTransaction { hash },
+    PolygonTransactionFailure { reason },
+}
+
+oneofs! {
+    polygon_nft_events { ImportedExternalCollection, ImportedExternalMint, TransferAssetSubmitted, CreateDropSubmitted, CreateDropFailed, MintDropSubmitted, MintDropFailed },
+}
+
+async fn imported_collection(cx: &EventContext, k: Key, v: PolygonCollection) -> Result<()> {
+    let collection = collections::ActiveModel {
+        name: Set(v.name),
+        blockchain: Set("Polygon".to_string()),
+        status: Set("ACTIVE".to_string()),
+        supply: Set(None),
+        seller_fee_basis_points: Set(None),
+        creator: Set(None),
+        ..Default::default()
+    };
+
+    cx.create_collection(&k.id, &k.project_id, collection).await
+}
+
+async fn imported_mint(cx: &EventContext, k: Key, v: PolygonCollectionMint) -> Result<()> {
+    cx.create_mint(&k.id, &k.project_id, &v.collection_id, "CREATED")
+        .await
+}
+
+async fn transfer_submitted(cx: &EventContext, k: Key, v: PolygonTransferAsset) -> Result<()> {
+    cx.record_transfer(
+        &k.id,
+        &k.project_id,
+        &v.collection_mint_id,
+        v.owner_address,
+        v.recipient_address,
+    )
+    .await
+}
+
+/// The drop's contract was deployed
+async fn create_drop_submitted(cx: &EventContext, k: Key, _: PolygonTransaction) -> Result<()> {
+    cx.change_collection(&k.id, CollectionChange::status("ACTIVE"))
+        .await
+}
+
+/// The drop's contract could not be deployed
+async fn create_drop_failed(cx: &EventContext, k: Key, _: PolygonTransactionFailure) -> Result<()> {
+    cx.change_collection(&k.id, CollectionChange::status("FAILED"))
+        .await
+}
+
+async fn mint_submitted(cx: &EventContext, k: Key, _: PolygonTransaction) -> Result<()> {
+    cx.set_mint_status(&k.id, "SUBMITTED".to_string()).await
+}
+
+async fn mint_failed(cx: &EventContext, k: Key, _: PolygonTransactionFailure) -> Result<()> {
+    cx.set_mint_status(&k.id, "FAILED".to_string()).await
+}
@@ -0,0 +1,352 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Debug,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        OnceLock,
+    },
+    time::{Duration, Instant},
+};
+
+use hub_core::{consumer::RecvError, prelude::*};
+use poem::async_trait;
+use serde_json::Value;
+
+use super::{
+    credentials::Credentials, credits::Credits, customers::Customers, nfts::Nfts,
+    organizations::Organizations, polygon_nfts::PolygonNfts, solana_nfts::SolanaNfts,
+    treasuries::Treasuries, webhooks::Webhooks, EventContext, EventHandler, MissingParent, Owner,
+};
+use crate::{db::Connection, journal::ToJson, Envelope};
+
+/// Declares the registered handlers, generating the list of their topics along with the
+/// registry
+macro_rules! handlers {
+    ($($handler:ident),+ $(,)?) => {
+        /// The topics of every registered handler
+        pub const TOPICS: &[&str] = &[$($handler::TOPIC),+];
+
+        /// The handlers every consumed topic is registered with
+        pub fn registry() -> &'static Registry {
+            static REGISTRY: OnceLock<Registry> = OnceLock::new();
+
+            REGISTRY.get_or_init(|| Registry::default()$(.register($handler))+)
+        }
+    };
+}
+
+handlers!(
+    Organizations,
+    Customers,
+    Treasuries,
+    Webhooks,
+    Credentials,
+    Credits,
+    Nfts,
+    SolanaNfts,
+    PolygonNfts,
+);
+
+/// Event handlers keyed by the topic they consume, along with the metrics of each of their
+/// events
+#[derive(Default)]
+pub struct Registry {
+    handlers: BTreeMap<&'static str, Registered>,
+}
+
+struct Registered {
+    handler: Box<dyn Dispatch>,
+    metrics: BTreeMap<&'static str, HandlerMetrics>,
+}
+
+impl Registry {
+    /// Adds `handler` for the messages of its topic
+    ///
+    /// # Panics
+    /// This function panics if a handler is already registered for the topic
+    #[must_use]
+    pub fn register<H: EventHandler>(mut self, handler: H) -> Self {
+        let registered = Registered {
+            handler: Box::new(handler),
+            metrics: H::EVENTS
+                .iter()
+                .map(|event| (*event, HandlerMetrics::default()))
+                .collect(),
+        };
+
+        assert!(
+            self.handlers.insert(H::TOPIC, registered).is_none(),
+            "a handler is already registered for {}",
+            H::TOPIC
+        );
+
+        self
+    }
+
+    /// Decodes `msg` with the handler registered for its topic
+    ///
+    /// # Errors
+    /// This function fails if no handler is registered for the topic or the message is not valid
+    /// protobuf
+    pub fn decode(&self, msg: &Envelope) -> Result<Box<dyn Decoded>, RecvError> {
+        self.handler(msg)?.handler.decode(msg)
+    }
+
+    /// Decodes `msg` and applies it with the handler registered for its topic. Events without a
+    /// projection are ignored.
+    ///
+    /// # Errors
+    /// This function fails if no handler is registered for the topic, the message cannot be
+    /// decoded or the handler fails
+    pub async fn process(&self, msg: &Envelope, db: &Connection) -> Result<()> {
+        let registered = self.handler(msg)?;
+
+        let cx = EventContext {
+            db: db.clone(),
+            timestamp: msg.metadata.timestamp,
+            offset: msg.metadata.offset,
+        };
+
+        registered
+            .handler
+            .dispatch(&cx, msg, &registered.metrics)
+            .await
+    }
+
+    /// The metrics of every handler, by topic and event
+    pub fn metrics(&self) -> impl Iterator<Item = (&'static str, &'static str, &HandlerMetrics)> {
+        self.handlers.iter().flat_map(|(topic, registered)| {
+            registered
+                .metrics
+                .iter()
+                .map(|(event, metrics)| (*topic, *event, metrics))
+        })
+    }
+
+    fn handler(&self, msg: &Envelope) -> Result<&Registered, RecvError> {
+        let topic = msg.metadata.topic.as_str();
+
+        self.handlers
+            .get(topic)
+            .ok_or_else(|| RecvError::BadTopic(topic.into()))
+    }
+}
+
+/// A message decoded by the handler of its topic
+pub trait Decoded: Debug + Send {
+    /// The name of the event, or `None` for events without a projection
+    fn event(&self) -> Option<&'static str>;
+
+    /// The key and event as JSON
+    fn to_json(&self) -> (Value, Value);
+
+    /// The organization and project the rows written for the message belong to
+    fn owner(&self) -> Owner;
+}
+
+struct TopicMessage<H: EventHandler> {
+    key: H::Key,
+    event: H::Event,
+}
+
+impl<H: EventHandler> Debug for TopicMessage<H> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct(H::TOPIC)
+            .field("key", &self.key)
+            .field("event", &self.event)
+            .finish()
+    }
+}
+
+impl<H: EventHandler> Decoded for TopicMessage<H> {
+    fn event(&self) -> Option<&'static str> {
+        H::event(&self.event)
+    }
+
+    fn to_json(&self) -> (Value, Value) {
+        (self.key.to_json(), self.event.to_json())
+    }
+
+    fn owner(&self) -> Owner {
+        H::owner(&self.key, &self.event)
+    }
+}
+
+/// An [`EventHandler`] with its key and event types erased so handlers of every topic can be
+/// stored together
+#[async_trait]
+trait Dispatch: Send + Sync {
+    fn decode(&self, msg: &Envelope) -> Result<Box<dyn Decoded>, RecvError>;
+
+    async fn dispatch(
+        &self,
+        cx: &EventContext,
+        msg: &Envelope,
+        metrics: &BTreeMap<&'static str, HandlerMetrics>,
+    ) -> Result<()>;
+}
+
+#[async_trait]
+impl<H: EventHandler> Dispatch for H {
+    fn decode(&self, msg: &Envelope) -> Result<Box<dyn Decoded>, RecvError> {
+        Ok(Box::new(TopicMessage::<H> {
+            key: H::Key::decode(msg.key.as_slice())?,
+            event: H::Event::decode(msg.payload.as_slice())?,
+        }))
+    }
+
+    async fn dispatch(
+        &self,
+        cx: &EventContext,
+        msg: &Envelope,
+        metrics: &BTreeMap<&'static str, HandlerMetrics>,
+    ) -> Result<()> {
+        let key = H::Key::decode(msg.key.as_slice())?;
+        let event = H::Event::decode(msg.payload.as_slice())?;
+
+        let Some(name) = H::event(&event) else {
+            return Ok(());
+        };
+
+        let start = Instant::now();
+        let res = self.handle(cx, key, event).await;
+
+        if let Some(metrics) = metrics.get(name) {
+            metrics.record(&res, start.elapsed());
+        }
+
+        res
+    }
+}
+
+/// Counters of the messages a handler has processed since the service started
+#[derive(Debug, Default)]
+pub struct HandlerMetrics {
+    applied: AtomicU64,
+    missing_parent: AtomicU64,
+    failed: AtomicU64,
+    micros: AtomicU64,
+}
+
+impl HandlerMetrics {
+    fn record(&self, res: &Result<()>, elapsed: Duration) {
+        let outcome = match res {
+            Ok(()) => &self.applied,
+            Err(e) if e.is::<MissingParent>() => &self.missing_parent,
+            Err(_) => &self.failed,
+        };
+
+        outcome.fetch_add(1, Ordering::Relaxed);
+        self.micros.fetch_add(
+            elapsed.as_micros().try_into().unwrap_or(u64::MAX),
+            Ordering::Relaxed,
+        );
+    }
+
+    /// Messages applied to the projections
+    #[must_use]
+    pub fn applied(&self) -> u64 {
+        self.applied.load(Ordering::Relaxed)
+    }
+
+    /// Messages that failed because a parent was not recorded yet
+    #[must_use]
+    pub fn missing_parent(&self) -> u64 {
+        self.missing_parent.load(Ordering::Relaxed)
+    }
+
+    /// Messages that failed for any other reason
+    #[must_use]
+    pub fn failed(&self) -> u64 {
+        self.failed.load(Ordering::Relaxed)
+    }
+
+    /// Time spent handling messages
+    #[must_use]
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_micros(self.micros.load(Ordering::Relaxed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hub_core::{chrono::NaiveDateTime, tokio};
+    use prost::Message;
+
+    use super::registry;
+    use crate::{
+        entities::organizations,
+        events::{test::*, MissingParent},
+        proto::{credits_event, CreditPurchase, CreditsEvent, CreditsEventKey},
+        Envelope, Metadata,
+    };
+
+    fn envelope(topic: &str, event: Option<credits_event::Event>) -> Envelope {
+        let key = CreditsEventKey {
+            id: "7eb0a199-d034-41e2-9264-c4d16083ef76".to_string(),
+            ..CreditsEventKey::default()
+        };
+
+        Envelope {
+            metadata: Metadata {
+                topic: topic.to_string(),
+                partition: 0,
+                offset: 0,
+                timestamp: NaiveDateTime::default(),
+            },
+            key: key.encode_to_vec(),
+            payload: CreditsEvent { event }.encode_to_vec(),
+        }
+    }
+
+    fn purchased() -> Option<credits_event::Event> {
+        Some(credits_event::Event::CreditsPurchased(CreditPurchase {
+            organization_id: ORGANIZATION.to_string(),
+            amount: 100,
+        }))
+    }
+
+    #[tokio::test(crate = "hub_core::tokio")]
+    async fn unknown_topic_is_rejected() {
+        let cx = context(mock());
+
+        let res = registry()
+            .process(&envelope("hub-unknown", purchased()), &cx.db)
+            .await;
+
+        assert!(res.is_err());
+        assert!(log(cx).is_empty());
+    }
+
+    #[tokio::test(crate = "hub_core::tokio")]
+    async fn unhandled_event_is_ignored() {
+        let cx = context(mock());
+        let other = credits_event::Event::Other(String::new());
+
+        registry()
+            .process(&envelope("hub-credits", Some(other)), &cx.db)
+            .await
+            .unwrap();
+
+        assert!(log(cx).is_empty());
+    }
+
+    #[tokio::test(crate = "hub_core::tokio")]
+    async fn handler_outcome_is_counted_per_event() {
+        let cx = context(mock().append_query_results([Vec::<organizations::Model>::new()]));
+        let metrics = registry()
+            .metrics()
+            .find(|(topic, event, _)| (*topic, *event) == ("hub-credits", "CreditsPurchased"))
+            .map(|(_, _, metrics)| metrics)
+            .unwrap();
+        let missing_parent = metrics.missing_parent();
+
+        let err = registry()
+            .process(&envelope("hub-credits", purchased()), &cx.db)
+            .await
+            .unwrap_err();
+
+        assert!(err.is::<MissingParent>());
+        assert_eq!(metrics.missing_parent(), missing_parent + 1);
+    }
+}
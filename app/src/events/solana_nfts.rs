@@ -0,0 +1,157 @@
+//! Imports and mint transactions on Solana
+
+use hub_core::prelude::*;
+use poem::async_trait;
+use sea_orm::Set;
+
+use super::{EventContext, EventHandler, Owner};
+use crate::{
+    entities::collections,
+    journal::{messages, oneofs},
+    proto::{
+        solana_nft_events::Event, Collection, CollectionImport, SolanaCompletedMintTransaction,
+        SolanaFailedTransaction, SolanaNftEventKey as Key, SolanaNftEvents,
+    },
+};
+
+/// Imports and mint transactions on Solana
+pub struct SolanaNfts;
+
+#[async_trait]
+impl EventHandler for SolanaNfts {
+    const TOPIC: &'static str = "hub-nfts-solana";
+    const EVENTS: &'static [&'static str] = &[
+        "ImportedExternalCollection",
+        "ImportedExternalMint",
+        "MintDropSubmitted",
+        "MintDropFailed",
+    ];
+
+    type Key = Key;
+    type Event = SolanaNftEvents;
+
+    fn event(v: &Self::Event) -> Option<&'static str> {
+        match &v.event {
+            Some(Event::ImportedExternalCollection(_)) => Some("ImportedExternalCollection"),
+            Some(Event::ImportedExternalMint(_)) => Some("ImportedExternalMint"),
+            Some(Event::MintDropSubmitted(_)) => Some("MintDropSubmitted"),
+            Some(Event::MintDropFailed(_)) => Some("MintDropFailed"),
+            _ => None,
+        }
+    }
+
+    fn owner(k: &Key, _: &Self::Event) -> Owner {
+        Owner::project(&k.project_id)
+    }
+
+    async fn handle(&self, cx: &EventContext, k: Key, v: Self::Event) -> Result<()> {
+        match v.event {
+            Some(Event::ImportedExternalCollection(v)) => imported_collection(cx, k, v).await,
+            Some(Event::ImportedExternalMint(v)) => imported_mint(cx, k, v).await,
+            Some(Event::MintDropSubmitted(v)) => mint_submitted(cx, k, v).await,
+            Some(Event::MintDropFailed(v)) => mint_failed(cx, k, v).await,
+            _ => Ok(()),
+        }
+    }
+}
+
+messages! {
+    SolanaNftEventKey { id, user_id, project_id },
+    SolanaNftEvents { event },
+    Metadata { name },
+    Collection { metadata },
+    CollectionImport { collection_id },
+    SolanaCompletedMintTransaction { signature, address },
+    SolanaFailedTransaction { reason },
+}
+
+oneofs! {
+    solana_nft_events { ImportedExternalCollection, ImportedExternalMint, MintDropSubmitted, MintDropFailed },
+}
+
+async fn imported_collection(cx: &EventContext, k: Key, v: Collection) -> Result<()> {
+    let collection = collections::ActiveModel {
+        name: Set(v.metadata.unwrap_or_default().name),
+        blockchain: Set("Solana".to_string()),
+        status: Set("ACTIVE".to_string()),
+        supply: Set(None),
+        seller_fee_basis_points: Set(None),
+        creator: Set(None),
+        ..Default::default()
+    };
+
+    cx.create_collection(&k.id, &k.project_id, collection).await
+}
+
+async fn imported_mint(cx: &EventContext, k: Key, v: CollectionImport) -> Result<()> {
+    cx.create_mint(&k.id, &k.project_id, &v.collection_id, "CREATED")
+        .await
+}
+
+async fn mint_submitted(
+    cx: &EventContext,
+    k: Key,
+    _: SolanaCompletedMintTransaction,
+) -> Result<()> {
+    cx.set_mint_status(&k.id, "SUBMITTED".to_string()).await
+}
+
+async fn mint_failed(cx: &EventContext, k: Key, _: SolanaFailedTransaction) -> Result<()> {
+    cx.set_mint_status(&k.id, "FAILED".to_string()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use hub_core::tokio;
+    use sea_orm::MockExecResult;
+
+    use super::{mint_failed, Key, SolanaFailedTransaction};
+    use crate::events::test::*;
+
+    #[tokio::test(crate = "hub_core::tokio")]
+    async fn failure_is_not_applied_over_later_statuses() {
+        let cx = context(
+            mock()
+                .append_query_results([[mint()]])
+                .append_exec_results([MockExecResult::default(), MockExecResult::default()]),
+        );
+        let key = Key {
+            id: MINT.to_string(),
+            project_id: PROJECT.to_string(),
+            ..Key::default()
+        };
+
+        mint_failed(&cx, key, SolanaFailedTransaction::default())
+            .await
+            .unwrap();
+
+        assert_eq!(log(cx), [
+            statement(
+                r#"SELECT "mints"."id", "mints"."project_id", "mints"."collection_id", "mints"."timestamp", "mints"."status" FROM "mints" WHERE "mints"."id" = $1 LIMIT $2"#,
+                [id(MINT), 1u64.into()],
+            ),
+            statement(
+                r#"INSERT INTO "mint_status_changes" ("mint_id", "status", "timestamp", "offset", "collection_id", "project_id") VALUES ($1, $2, $3, $4, $5, $6) ON CONFLICT ("mint_id", "status", "timestamp", "offset") DO NOTHING"#,
+                [
+                    id(MINT),
+                    "FAILED".into(),
+                    timestamp().into(),
+                    0i64.into(),
+                    id(COLLECTION),
+                    id(PROJECT),
+                ],
+            ),
+            statement(
+                r#"UPDATE "mints" SET "status" = $1 WHERE "mints"."id" = $2 AND "mints"."id" NOT IN (SELECT "mint_status_changes"."mint_id" FROM "mint_status_changes" WHERE "mint_status_changes"."mint_id" = $3 AND ("mint_status_changes"."timestamp" > $4 OR ("mint_status_changes"."timestamp" = $5 AND "mint_status_changes"."offset" > $6)))"#,
+                [
+                    "FAILED".into(),
+                    id(MINT),
+                    id(MINT),
+                    timestamp().into(),
+                    timestamp().into(),
+                    0i64.into(),
+                ],
+            ),
+        ]);
+    }
+}
@@ -0,0 +1,132 @@
+//! Treasuries and wallets of projects and their customers
+
+use hub_core::prelude::*;
+use poem::async_trait;
+use sea_orm::Set;
+
+use super::{int_to_blockchain, parse_id, EventContext, EventHandler, Owner};
+use crate::{
+    entities::{customers, projects, treasuries, wallets},
+    journal::{messages, oneofs},
+    proto::{
+        treasury_events::Event, CustomerTreasury, CustomerWallet, ProjectTreasury, ProjectWallet,
+        TreasuryEventKey as Key, TreasuryEvents,
+    },
+};
+
+/// Treasuries and wallets of projects and their customers
+pub struct Treasuries;
+
+#[async_trait]
+impl EventHandler for Treasuries {
+    const TOPIC: &'static str = "hub-treasuries";
+    const EVENTS: &'static [&'static str] = &[
+        "CustomerWalletCreated",
+        "ProjectWalletCreated",
+        "ProjectTreasuryCreated",
+        "CustomerTreasuryCreated",
+    ];
+
+    type Key = Key;
+    type Event = TreasuryEvents;
+
+    fn event(v: &Self::Event) -> Option<&'static str> {
+        match &v.event {
+            Some(Event::CustomerWalletCreated(_)) => Some("CustomerWalletCreated"),
+            Some(Event::ProjectWalletCreated(_)) => Some("ProjectWalletCreated"),
+            Some(Event::ProjectTreasuryCreated(_)) => Some("ProjectTreasuryCreated"),
+            Some(Event::CustomerTreasuryCreated(_)) => Some("CustomerTreasuryCreated"),
+            _ => None,
+        }
+    }
+
+    fn owner(k: &Key, v: &Self::Event) -> Owner {
+        match &v.event {
+            Some(Event::ProjectWalletCreated(w)) => Owner::project(&w.project_id),
+            Some(Event::ProjectTreasuryCreated(t)) => Owner::project(&t.project_id),
+            Some(Event::CustomerTreasuryCreated(t)) => Owner::project(&t.project_id),
+            _ => Owner::project(&k.project_id),
+        }
+    }
+
+    async fn handle(&self, cx: &EventContext, k: Key, v: Self::Event) -> Result<()> {
+        match v.event {
+            Some(Event::CustomerWalletCreated(v)) => customer_wallet_created(cx, k, v).await,
+            Some(Event::ProjectWalletCreated(v)) => project_wallet_created(cx, k, v).await,
+            Some(Event::ProjectTreasuryCreated(v)) => project_treasury_created(cx, k, v).await,
+            Some(Event::CustomerTreasuryCreated(v)) => customer_treasury_created(cx, k, v).await,
+            _ => Ok(()),
+        }
+    }
+}
+
+messages! {
+    TreasuryEventKey { id, user_id, project_id },
+    TreasuryEvents { event },
+    CustomerWallet { project_id, customer_id, blockchain },
+    ProjectWallet { project_id, wallet_address, blockchain },
+    ProjectTreasury { project_id },
+    CustomerTreasury { project_id, customer_id },
+}
+
+oneofs! {
+    treasury_events { CustomerWalletCreated, ProjectWalletCreated, ProjectTreasuryCreated, CustomerTreasuryCreated },
+}
+
+async fn customer_wallet_created(cx: &EventContext, k: Key, v: CustomerWallet) -> Result<()> {
+    cx.upsert(
+        wallets::ActiveModel {
+            id: Set(parse_id(&k.id)?),
+            project_id: Set(cx.parent::<projects::Entity>(&k.project_id).await?),
+            customer_id: Set(Some(cx.parent::<customers::Entity>(&v.customer_id).await?)),
+            blockchain: Set(int_to_blockchain(v.blockchain)),
+            timestamp: Set(cx.timestamp),
+            kind: Set("CUSTOMER".to_string()),
+        },
+        &[],
+    )
+    .await
+}
+
+async fn project_wallet_created(cx: &EventContext, k: Key, v: ProjectWallet) -> Result<()> {
+    cx.upsert(
+        wallets::ActiveModel {
+            id: Set(parse_id(&k.id)?),
+            project_id: Set(cx.parent::<projects::Entity>(&v.project_id).await?),
+            customer_id: Set(None),
+            blockchain: Set(int_to_blockchain(v.blockchain)),
+            timestamp: Set(cx.timestamp),
+            kind: Set("PROJECT".to_string()),
+        },
+        &[],
+    )
+    .await
+}
+
+async fn project_treasury_created(cx: &EventContext, k: Key, v: ProjectTreasury) -> Result<()> {
+    cx.upsert(
+        treasuries::ActiveModel {
+            id: Set(parse_id(&k.id)?),
+            project_id: Set(cx.parent::<projects::Entity>(&v.project_id).await?),
+            customer_id: Set(None),
+            kind: Set("PROJECT".to_string()),
+            timestamp: Set(cx.timestamp),
+        },
+        &[],
+    )
+    .await
+}
+
+async fn customer_treasury_created(cx: &EventContext, k: Key, v: CustomerTreasury) -> Result<()> {
+    cx.upsert(
+        treasuries::ActiveModel {
+            id: Set(parse_id(&k.id)?),
+            project_id: Set(cx.parent::<projects::Entity>(&v.project_id).await?),
+            customer_id: Set(Some(cx.parent::<customers::Entity>(&v.customer_id).await?)),
+            kind: Set("CUSTOMER".to_string()),
+            timestamp: Set(cx.timestamp),
+        },
+        &[],
+    )
+    .await
+}
@@ -0,0 +1,166 @@
+//! Webhooks of an organization's projects
+
+use hub_core::prelude::*;
+use poem::async_trait;
+use sea_orm::{prelude::*, sea_query::Expr, Set};
+
+use super::{parse_id, EventContext, EventHandler, Owner};
+use crate::{
+    entities::{organizations, projects, webhooks},
+    journal::{messages, oneofs},
+    proto::{webhook_events::Event, Webhook, WebhookEventKey as Key, WebhookEvents},
+};
+
+/// Webhooks of an organization's projects
+pub struct Webhooks;
+
+#[async_trait]
+impl EventHandler for Webhooks {
+    const TOPIC: &'static str = "hub-webhooks";
+    const EVENTS: &'static [&'static str] = &["WebhookCreated", "WebhookUpdated", "WebhookDeleted"];
+
+    type Key = Key;
+    type Event = WebhookEvents;
+
+    fn event(v: &Self::Event) -> Option<&'static str> {
+        match &v.event {
+            Some(Event::WebhookCreated(_)) => Some("WebhookCreated"),
+            Some(Event::WebhookUpdated(_)) => Some("WebhookUpdated"),
+            Some(Event::WebhookDeleted(_)) => Some("WebhookDeleted"),
+            _ => None,
+        }
+    }
+
+    fn owner(_: &Key, v: &Self::Event) -> Owner {
+        match &v.event {
+            Some(
+                Event::WebhookCreated(w) | Event::WebhookUpdated(w) | Event::WebhookDeleted(w),
+            ) => Owner::new(&w.organization_id, &w.project_id),
+            _ => Owner::default(),
+        }
+    }
+
+    async fn handle(&self, cx: &EventContext, k: Key, v: Self::Event) -> Result<()> {
+        match v.event {
+            Some(Event::WebhookCreated(v) | Event::WebhookUpdated(v)) => saved(cx, k, v).await,
+            Some(Event::WebhookDeleted(v)) => deleted(cx, k, v).await,
+            _ => Ok(()),
+        }
+    }
+}
+
+messages! {
+    WebhookEventKey { id, user_id },
+    WebhookEvents { event },
+    Webhook { project_id, organization_id },
+}
+
+oneofs! {
+    webhook_events { WebhookCreated, WebhookUpdated, WebhookDeleted },
+}
+
+/// A webhook was created or updated
+async fn saved(cx: &EventContext, k: Key, v: Webhook) -> Result<()> {
+    cx.upsert(
+        webhooks::ActiveModel {
+            id: Set(parse_id(&k.id)?),
+            project_id: Set(cx.parent::<projects::Entity>(&v.project_id).await?),
+            organization_id: Set(cx
+                .parent::<organizations::Entity>(&v.organization_id)
+                .await?),
+            timestamp: Set(cx.timestamp),
+            deleted_at: Set(None),
+        },
+        &[
+            webhooks::Column::ProjectId,
+            webhooks::Column::OrganizationId,
+        ],
+    )
+    .await
+}
+
+/// Only sets the deletion time, a webhook deleted before its creation was processed waits for
+/// it rather than being recorded as created at the time of deletion
+async fn deleted(cx: &EventContext, k: Key, _: Webhook) -> Result<()> {
+    let webhook = cx.find::<webhooks::Entity>(&k.id).await?;
+
+    webhooks::Entity::update_many()
+        .col_expr(webhooks::Column::DeletedAt, Expr::value(Some(cx.timestamp)))
+        .filter(webhooks::Column::Id.eq(webhook.id))
+        .exec(cx.db.get())
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use hub_core::tokio;
+    use sea_orm::MockExecResult;
+
+    use super::{deleted, saved, DateTime, Key, Webhook};
+    use crate::{
+        entities::{organizations, webhooks},
+        events::{test::*, MissingParent},
+    };
+
+    const WEBHOOK: &str = "6daf9088-cf23-40d1-8153-b3c05f72de65";
+
+    #[tokio::test(crate = "hub_core::tokio")]
+    async fn saved_updates_owners() {
+        let organization = organizations::Model {
+            id: ORGANIZATION.parse().unwrap(),
+            name: "Holaplex".to_string(),
+        };
+        let cx = context(
+            mock()
+                .append_query_results([[project()]])
+                .append_query_results([[organization]])
+                .append_exec_results([MockExecResult::default()]),
+        );
+        let key = Key {
+            id: WEBHOOK.to_string(),
+            ..Key::default()
+        };
+        let webhook = Webhook {
+            project_id: PROJECT.to_string(),
+            organization_id: ORGANIZATION.to_string(),
+        };
+
+        saved(&cx, key, webhook).await.unwrap();
+
+        assert_eq!(log(cx), [
+            find_project(),
+            statement(
+                r#"SELECT "organizations"."id", "organizations"."name" FROM "organizations" WHERE "organizations"."id" = $1 LIMIT $2"#,
+                [id(ORGANIZATION), 1u64.into()],
+            ),
+            statement(
+                r#"INSERT INTO "webhooks" ("id", "project_id", "organization_id", "timestamp", "deleted_at") VALUES ($1, $2, $3, $4, $5) ON CONFLICT ("id") DO UPDATE SET "project_id" = "excluded"."project_id", "organization_id" = "excluded"."organization_id""#,
+                [
+                    id(WEBHOOK),
+                    id(PROJECT),
+                    id(ORGANIZATION),
+                    timestamp().into(),
+                    None::<DateTime>.into(),
+                ],
+            ),
+        ]);
+    }
+
+    #[tokio::test(crate = "hub_core::tokio")]
+    async fn deleted_before_created_is_missing_parent() {
+        let cx = context(mock().append_query_results([Vec::<webhooks::Model>::new()]));
+        let key = Key {
+            id: WEBHOOK.to_string(),
+            ..Key::default()
+        };
+
+        let err = deleted(&cx, key, Webhook::default()).await.unwrap_err();
+        let missing = err.downcast_ref::<MissingParent>().unwrap();
+
+        assert_eq!(missing.table, "webhooks");
+        assert_eq!(missing.id.to_string(), WEBHOOK);
+        assert_eq!(log(cx).len(), 1);
+    }
+}
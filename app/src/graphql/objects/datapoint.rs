@@ -2,9 +2,10 @@ use std::{fmt, str::FromStr};
 
 use async_graphql::{Enum, Error, InputObject, SimpleObject};
 pub use cube_client::models::{v1_time::TimeGranularity, V1LoadResponse};
+use either::Either;
 use hub_core::{
     anyhow::Result,
-    chrono::{NaiveDate, NaiveDateTime},
+    chrono::{Datelike, Duration, Months, NaiveDate, NaiveDateTime, NaiveTime, Timelike},
     uuid::Uuid,
 };
 use serde::{Deserialize, Serialize};
@@ -34,6 +35,18 @@ pub struct DataPoint {
     pub credits: Option<Vec<Data>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub transfers: Option<Vec<Data>>,
+    /// Analytics data for organization members.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub members: Option<Vec<Data>>,
+    /// Analytics data for the invites sent to join organizations.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub invites: Option<Vec<Data>>,
+    /// Analytics data for API credentials.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub credentials: Option<Vec<Data>>,
+    /// Analytics data for the status, name and supply changes of collections.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collection_history: Option<Vec<Data>>,
     #[graphql(visible = false)]
     pub timestamp: Option<NaiveDateTime>,
 }
@@ -76,6 +89,10 @@ impl DataPoint {
             transfers: None,
             webhooks: None,
             credits: None,
+            members: None,
+            invites: None,
+            credentials: None,
+            collection_history: None,
             timestamp: None,
         }
     }
@@ -93,9 +110,31 @@ impl DataPoint {
             (Projects, projects),
             (Transfers, transfers),
             (Webhooks, webhooks),
-            (Credits, credits)
+            (Credits, credits),
+            (Members, members),
+            (Invites, invites),
+            (Credentials, credentials),
+            (CollectionHistory, collection_history)
         );
     }
+    /// The data of `resource`, if any
+    pub fn data_mut(&mut self, resource: Resource) -> Option<&mut Vec<Data>> {
+        match resource {
+            Resource::Mints => self.mints.as_mut(),
+            Resource::Customers => self.customers.as_mut(),
+            Resource::Wallets => self.wallets.as_mut(),
+            Resource::Collections => self.collections.as_mut(),
+            Resource::Projects => self.projects.as_mut(),
+            Resource::Transfers => self.transfers.as_mut(),
+            Resource::Webhooks => self.webhooks.as_mut(),
+            Resource::Credits => self.credits.as_mut(),
+            Resource::Members => self.members.as_mut(),
+            Resource::Invites => self.invites.as_mut(),
+            Resource::Credentials => self.credentials.as_mut(),
+            Resource::CollectionHistory => self.collection_history.as_mut(),
+        }
+    }
+
     pub fn merge(&mut self, other: &DataPoint) {
         merge_fields!(
             self,
@@ -107,7 +146,11 @@ impl DataPoint {
             projects,
             transfers,
             webhooks,
-            credits
+            credits,
+            members,
+            invites,
+            credentials,
+            collection_history
         );
     }
 }
@@ -126,12 +169,121 @@ pub struct Data {
     /// The ID of the project the data belongs to.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub project_id: Option<Uuid>,
+    /// The ID of the customer the data belongs to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub customer_id: Option<Uuid>,
+    /// The status of the mint or collection the data belongs to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    /// Whether the wallet belongs to a customer or a project treasury.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    /// The blockchain the data belongs to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blockchain: Option<String>,
+    /// The wallet address a transfer was sent from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sender: Option<String>,
+    /// The wallet address a transfer was sent to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recipient: Option<String>,
+    /// Total supply of the collection, empty for open editions.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supply: Option<u64>,
+    /// Supply of the collection left to mint.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remaining_supply: Option<u64>,
+    /// Share of the collection supply that has been minted, between 0 and 1.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sell_through: Option<f64>,
+    /// Share of mints that failed, between 0 and 1.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failure_rate: Option<f64>,
+    /// Change of the count versus the previous period of the same length.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change: Option<Change>,
+    /// Running total of the count from the first record to the end of the bucket, or of the
+    /// period when the data is not grouped by time. Not available for `ACTIVE` counts.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<u64>,
     /// the timestamp associated with the data point.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub timestamp: Option<NaiveDateTime>,
 }
 
-#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+impl Data {
+    /// Data of the same group with a zero count, for a bucket without records
+    #[must_use]
+    pub fn empty(&self, timestamp: NaiveDateTime) -> Self {
+        Self {
+            count: Some(0),
+            remaining_supply: None,
+            sell_through: None,
+            failure_rate: None,
+            change: None,
+            total: None,
+            timestamp: Some(timestamp),
+            ..self.clone()
+        }
+    }
+
+    /// The dimension values identifying the group the data belongs to
+    #[must_use]
+    pub fn group(&self) -> Group {
+        Group {
+            organization_id: self.organization_id,
+            collection_id: self.collection_id,
+            project_id: self.project_id,
+            customer_id: self.customer_id,
+            status: self.status.clone(),
+            kind: self.kind.clone(),
+            blockchain: self.blockchain.clone(),
+            sender: self.sender.clone(),
+            recipient: self.recipient.clone(),
+            supply: self.supply,
+        }
+    }
+}
+
+/// The dimension values of a [`Data`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Group {
+    organization_id: Option<Uuid>,
+    collection_id: Option<Uuid>,
+    project_id: Option<Uuid>,
+    customer_id: Option<Uuid>,
+    status: Option<String>,
+    kind: Option<String>,
+    blockchain: Option<String>,
+    sender: Option<String>,
+    recipient: Option<String>,
+    supply: Option<u64>,
+}
+
+/// The change of a count versus the previous period of the same length.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, SimpleObject)]
+pub struct Change {
+    /// The count minus the count of the previous period.
+    pub absolute: i64,
+    /// The absolute change as a percentage of the previous count, empty when it was zero.
+    pub percent: Option<f64>,
+}
+
+impl Change {
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn between(previous: u64, current: u64) -> Self {
+        let absolute = i128::from(current) - i128::from(previous);
+
+        Self {
+            absolute: i64::try_from(absolute).unwrap_or(i64::MAX),
+            percent: (previous > 0).then(|| absolute as f64 / previous as f64 * 100.0),
+        }
+    }
+}
+
+/// The size of the time buckets data points are grouped by.
+#[derive(Debug, Enum, Copy, Clone, Eq, PartialEq)]
 pub enum Granularity {
     Hour,
     Day,
@@ -145,12 +297,72 @@ impl fmt::Display for Granularity {
         let s = match self {
             Granularity::Hour => "hour",
             Granularity::Day => "day",
-            _ => "week",
+            Granularity::Week => "week",
+            Granularity::Month => "month",
+            Granularity::Year => "year",
         };
         write!(f, "{s}")
     }
 }
 
+impl Granularity {
+    /// Start of the bucket containing `timestamp`. Weeks start on Monday.
+    #[must_use]
+    pub fn truncate(self, timestamp: NaiveDateTime) -> Option<NaiveDateTime> {
+        let date = timestamp.date();
+
+        match self {
+            Granularity::Hour => date.and_hms_opt(timestamp.hour(), 0, 0),
+            Granularity::Day => Some(NaiveDateTime::new(date, NaiveTime::default())),
+            Granularity::Week => {
+                let days = date.weekday().num_days_from_monday();
+
+                Some(NaiveDateTime::new(date, NaiveTime::default()) - Duration::days(days.into()))
+            },
+            Granularity::Month => Some(NaiveDateTime::new(date.with_day(1)?, NaiveTime::default())),
+            Granularity::Year => Some(NaiveDateTime::new(
+                date.with_ordinal(1)?,
+                NaiveTime::default(),
+            )),
+        }
+    }
+
+    /// Start of the bucket following the one starting at `timestamp`
+    #[must_use]
+    pub fn next(self, timestamp: NaiveDateTime) -> Option<NaiveDateTime> {
+        match self {
+            Granularity::Hour => timestamp.checked_add_signed(Duration::hours(1)),
+            Granularity::Day => timestamp.checked_add_signed(Duration::days(1)),
+            Granularity::Week => timestamp.checked_add_signed(Duration::weeks(1)),
+            Granularity::Month => timestamp.checked_add_months(Months::new(1)),
+            Granularity::Year => timestamp.checked_add_months(Months::new(12)),
+        }
+    }
+
+    /// Start of the bucket preceding the one starting at `timestamp`
+    #[must_use]
+    pub fn previous(self, timestamp: NaiveDateTime) -> Option<NaiveDateTime> {
+        match self {
+            Granularity::Hour => timestamp.checked_sub_signed(Duration::hours(1)),
+            Granularity::Day => timestamp.checked_sub_signed(Duration::days(1)),
+            Granularity::Week => timestamp.checked_sub_signed(Duration::weeks(1)),
+            Granularity::Month => timestamp.checked_sub_months(Months::new(1)),
+            Granularity::Year => timestamp.checked_sub_months(Months::new(12)),
+        }
+    }
+
+    /// Hours in the shortest bucket of this granularity
+    fn min_hours(self) -> i64 {
+        match self {
+            Granularity::Hour => 1,
+            Granularity::Day => 24,
+            Granularity::Week => 7 * 24,
+            Granularity::Month => 28 * 24,
+            Granularity::Year => 365 * 24,
+        }
+    }
+}
+
 #[derive(InputObject)]
 pub struct Measure {
     pub resource: Resource,
@@ -175,6 +387,9 @@ impl Measure {
 pub enum Operation {
     Count,
     Change,
+    FailureRate,
+    RemainingSupply,
+    SellThrough,
 }
 
 impl fmt::Display for Operation {
@@ -182,6 +397,9 @@ impl fmt::Display for Operation {
         let s = match self {
             Operation::Count => "count",
             Operation::Change => "change",
+            Operation::FailureRate => "failure_rate",
+            Operation::RemainingSupply => "remaining_supply",
+            Operation::SellThrough => "sell_through",
         };
         write!(f, "{s}")
     }
@@ -197,6 +415,10 @@ pub enum Resource {
     Transfers,
     Webhooks,
     Credits,
+    Members,
+    Invites,
+    Credentials,
+    CollectionHistory,
 }
 
 impl fmt::Display for Resource {
@@ -210,11 +432,52 @@ impl fmt::Display for Resource {
             Resource::Transfers => "transfers",
             Resource::Webhooks => "webhooks",
             Resource::Credits => "credits",
+            Resource::Members => "members",
+            Resource::Invites => "invites",
+            Resource::Credentials => "credentials",
+            Resource::CollectionHistory => "collection_history",
         };
         write!(f, "{s}")
     }
 }
 
+impl Resource {
+    /// Whether `field` of [`Data`] has a Cube member for the resource
+    #[must_use]
+    pub fn supports(self, field: &str) -> bool {
+        match field {
+            "failureRate" => self == Resource::Mints,
+            "sender" | "recipient" => self == Resource::Transfers,
+            "kind" | "customerId" => self == Resource::Wallets,
+            "blockchain" => matches!(self, Resource::Wallets | Resource::Collections),
+            "remainingSupply" | "sellThrough" => self == Resource::Collections,
+            "supply" => matches!(self, Resource::Collections | Resource::CollectionHistory),
+            "status" => matches!(
+                self,
+                Resource::Mints
+                    | Resource::Collections
+                    | Resource::CollectionHistory
+                    | Resource::Members
+            ),
+            _ => true,
+        }
+    }
+
+    /// Whether the resource has an `active_until` Cube member, the time it was deleted or
+    /// deactivated
+    #[must_use]
+    pub fn tracks_activity(self) -> bool {
+        matches!(
+            self,
+            Resource::Projects
+                | Resource::Customers
+                | Resource::Webhooks
+                | Resource::Members
+                | Resource::Credentials
+        )
+    }
+}
+
 impl FromStr for Resource {
     type Err = ();
 
@@ -228,11 +491,26 @@ impl FromStr for Resource {
             "transfers" => Ok(Resource::Transfers),
             "webhooks" => Ok(Resource::Webhooks),
             "credits" => Ok(Resource::Credits),
+            "members" => Ok(Resource::Members),
+            "invites" => Ok(Resource::Invites),
+            "credentials" => Ok(Resource::Credentials),
+            "collectionHistory" => Ok(Resource::CollectionHistory),
             _ => Err(()),
         }
     }
 }
 
+/// Whether `count` is the number of records created during the period or the number still
+/// active, neither deleted nor deactivated, at its end.
+///
+/// Only resources recording when they are deleted or deactivated can be counted as `ACTIVE`.
+#[derive(Default, Enum, Copy, Clone, Eq, PartialEq)]
+pub enum CountMode {
+    #[default]
+    Created,
+    Active,
+}
+
 #[derive(Enum, Copy, Clone, Eq, PartialEq)]
 pub enum Order {
     Asc,
@@ -267,14 +545,18 @@ impl fmt::Display for Dimension {
     }
 }
 
+/// A period to query, either between two dates or relative to today.
 #[derive(InputObject)]
 pub struct DateRange {
+    /// The first day of the period, inclusive.
     pub start: Option<NaiveDate>,
+    /// The last day of the period, inclusive.
     pub end: Option<NaiveDate>,
+    /// A period relative to today, instead of `start` and `end`.
     pub interval: Option<Interval>,
 }
 
-#[derive(Default, Enum, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Default, Enum, Copy, Clone, Eq, PartialEq)]
 pub enum Interval {
     All,
     #[default]
@@ -311,6 +593,50 @@ impl fmt::Display for Interval {
     }
 }
 impl Interval {
+    /// The most days the interval covers, or `None` when it is unbounded
+    #[must_use]
+    pub fn max_days(&self) -> Option<i64> {
+        match self {
+            Interval::All => None,
+            Interval::Today | Interval::Yesterday => Some(1),
+            Interval::ThisWeek | Interval::Last7Days | Interval::LastWeek => Some(7),
+            Interval::Last30Days => Some(30),
+            Interval::ThisMonth | Interval::LastMonth => Some(31),
+            Interval::LastQuarter => Some(92),
+            Interval::ThisYear | Interval::LastYear => Some(366),
+        }
+    }
+
+    /// The first and last day of the interval relative to `today`, or `None` when it is
+    /// unbounded
+    #[must_use]
+    pub fn bounds(&self, today: NaiveDate) -> Option<(NaiveDate, NaiveDate)> {
+        let day = Duration::days(1);
+        let week = today - Duration::days(today.weekday().num_days_from_monday().into());
+        let month = today.with_day(1)?;
+        let last_month = (month - day).with_day(1)?;
+        let quarter = month.with_month((month.month0() / 3) * 3 + 1)?;
+        let last_quarter = (quarter - day).with_day(1)?;
+        let last_quarter = last_quarter.with_month((last_quarter.month0() / 3) * 3 + 1)?;
+        let year = today.with_ordinal(1)?;
+        let last_year = (year - day).with_ordinal(1)?;
+
+        Some(match self {
+            Interval::All => return None,
+            Interval::Today => (today, today),
+            Interval::Yesterday => (today - day, today - day),
+            Interval::ThisWeek => (week, week + Duration::days(6)),
+            Interval::LastWeek => (week - Duration::days(7), week - day),
+            Interval::Last7Days => (today - Duration::days(6), today),
+            Interval::Last30Days => (today - Duration::days(29), today),
+            Interval::ThisMonth => (month, month.checked_add_months(Months::new(1))? - day),
+            Interval::LastMonth => (last_month, month - day),
+            Interval::LastQuarter => (last_quarter, quarter - day),
+            Interval::ThisYear => (year, year.with_year(year.year() + 1)? - day),
+            Interval::LastYear => (last_year, year - day),
+        })
+    }
+
     #[must_use]
     pub fn to_granularity(&self) -> Granularity {
         match self {
@@ -327,22 +653,287 @@ impl Interval {
         }
     }
 }
-impl From<DateRange> for Vec<String> {
-    fn from(date_range: DateRange) -> Self {
-        vec![
-            date_range.start.unwrap().format("%Y-%m-%d").to_string(),
-            date_range.end.unwrap().format("%Y-%m-%d").to_string(),
-        ]
+
+impl DateRange {
+    /// Longest span, in days, an explicit date range may cover
+    pub const MAX_DAYS: i64 = 366;
+
+    /// Checks that the range is either a relative `interval` or both a `start` and `end`, with
+    /// `start` not after `end` and spanning at most [`MAX_DAYS`](Self::MAX_DAYS) days.
+    ///
+    /// # Errors
+    /// This function returns an error if the range is incomplete, ambiguous or too long
+    pub fn validate(self) -> Result<Period, Error> {
+        match self {
+            Self {
+                start: Some(start),
+                end: Some(end),
+                interval: None,
+            } => {
+                if start > end {
+                    return Err(Error::new(format!(
+                        "dateRange start {start} is after its end {end}"
+                    )));
+                }
+
+                let days = (end - start).num_days() + 1;
+
+                if days > Self::MAX_DAYS {
+                    return Err(Error::new(format!(
+                        "dateRange spans {days} days, the maximum is {}",
+                        Self::MAX_DAYS
+                    )));
+                }
+
+                Ok(Period::Between { start, end })
+            },
+            Self {
+                start: None,
+                end: None,
+                interval: Some(interval),
+            } => Ok(Period::Relative(interval)),
+            _ => Err(Error::new(
+                "dateRange requires either both a start and an end, or an interval",
+            )),
+        }
+    }
+}
+
+/// The period an analytics query covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    /// Relative to the current date
+    Relative(Interval),
+    /// From the start of `start` to the end of `end`
+    Between { start: NaiveDate, end: NaiveDate },
+}
+
+impl Period {
+    /// Most data points a query may return per resource
+    pub const MAX_POINTS: i64 = 1000;
+
+    /// Resolves the period from the `interval` and `dateRange` arguments, defaulting to
+    /// [`Interval::Today`] when neither is given.
+    ///
+    /// # Errors
+    /// This function returns an error if both are given or the date range is invalid
+    pub fn from_args(
+        interval: Option<Interval>,
+        date_range: Option<DateRange>,
+    ) -> Result<Self, Error> {
+        match (interval, date_range) {
+            (Some(_), Some(_)) => Err(Error::new("interval and dateRange cannot be used together")),
+            (interval, None) => Ok(Self::Relative(interval.unwrap_or_default())),
+            (None, Some(date_range)) => date_range.validate(),
+        }
+    }
+
+    /// The period of a relative interval resolved against `today` into the days it covers, so
+    /// Cube is sent the same days the buckets and comparisons are computed for. An unbounded
+    /// period stays relative.
+    #[must_use]
+    pub fn resolve(self, today: NaiveDate) -> Period {
+        self.bounds(today)
+            .map_or(self, |(start, end)| Self::Between { start, end })
+    }
+
+    /// The Cube `dateRange` of the time dimension. Only an unbounded period is sent relative once
+    /// the period is [`resolve`](Self::resolve)d.
+    #[must_use]
+    pub fn date_range(&self) -> Either<String, Vec<String>> {
+        match self {
+            Self::Relative(interval) => Either::Left(interval.to_string()),
+            Self::Between { start, end } => Either::Right(vec![
+                start.format("%Y-%m-%d").to_string(),
+                end.format("%Y-%m-%d").to_string(),
+            ]),
+        }
+    }
+
+    /// The granularity data points are grouped by: `granularity` when requested, after checking
+    /// it doesn't split the period into more than [`MAX_POINTS`](Self::MAX_POINTS) buckets, or
+    /// one chosen so the period has a readable number of points.
+    ///
+    /// # Errors
+    /// This function returns an error if `granularity` is too fine for the period
+    pub fn granularity(&self, granularity: Option<Granularity>) -> Result<Granularity, Error> {
+        let Some(granularity) = granularity else {
+            return Ok(self.to_granularity());
+        };
+
+        let max_points = self
+            .max_days()
+            .map(|days| days * 24 / granularity.min_hours() + 1);
+
+        match max_points {
+            Some(points) if points <= Self::MAX_POINTS => Ok(granularity),
+            Some(points) => Err(Error::new(format!(
+                "granularity {granularity} splits the period into up to {points} data points, the \
+                 maximum is {}",
+                Self::MAX_POINTS
+            ))),
+            None if granularity.min_hours() >= Granularity::Month.min_hours() => Ok(granularity),
+            None => Err(Error::new(format!(
+                "granularity {granularity} is too fine for an unbounded period, use month or year"
+            ))),
+        }
+    }
+
+    /// The period of the same length ending the day before this one starts, or `None` when this
+    /// period is unbounded or starts after `today`. A period ending after `today`, such as
+    /// [`Interval::ThisMonth`], is compared by the part elapsed so far. Relative intervals are
+    /// resolved against `today`.
+    #[must_use]
+    pub fn previous(&self, today: NaiveDate) -> Option<Period> {
+        let (start, end) = self.bounds(today)?;
+        let end = end.min(today);
+
+        if end < start {
+            return None;
+        }
+
+        let days = end - start + Duration::days(1);
+
+        Some(Self::Between {
+            start: start - days,
+            end: end - days,
+        })
+    }
+
+    /// This period extended back to the start of the `granularity` bucket preceding its first
+    /// bucket, so the first bucket has one to be compared with, along with the start of the first
+    /// bucket. `None` when the period is unbounded.
+    #[must_use]
+    pub fn with_previous_bucket(
+        &self,
+        granularity: Granularity,
+        today: NaiveDate,
+    ) -> Option<(Period, NaiveDateTime)> {
+        let (start, end) = self.bounds(today)?;
+        let first = granularity.truncate(NaiveDateTime::new(start, NaiveTime::default()))?;
+        let previous = granularity.previous(first)?;
+
+        Some((
+            Self::Between {
+                start: previous.date(),
+                end,
+            },
+            first,
+        ))
+    }
+
+    /// The last day of the period, or `today` when the period is unbounded or ends later
+    #[must_use]
+    pub fn end(&self, today: NaiveDate) -> NaiveDate {
+        self.bounds(today).map_or(today, |(_, end)| end.min(today))
+    }
+
+    /// Everything from the Unix epoch to the [`end`](Self::end) of this period
+    #[must_use]
+    pub fn until_end(&self, today: NaiveDate) -> Period {
+        Self::Between {
+            start: NaiveDate::from_ymd_opt(1970, 1, 1).unwrap_or(NaiveDate::MIN),
+            end: self.end(today),
+        }
+    }
+
+    /// Everything from the Unix epoch to the day before this period starts, or `None` when this
+    /// period is unbounded. Relative intervals are resolved against `today`.
+    #[must_use]
+    pub fn before(&self, today: NaiveDate) -> Option<Period> {
+        let (start, _) = self.bounds(today)?;
+
+        Some(Self::Between {
+            start: NaiveDate::from_ymd_opt(1970, 1, 1)?,
+            end: start.pred_opt()?,
+        })
+    }
+
+    /// The first and last day of the period, or `None` when it is unbounded. Relative intervals
+    /// are resolved against `today`.
+    #[must_use]
+    pub fn bounds(&self, today: NaiveDate) -> Option<(NaiveDate, NaiveDate)> {
+        match *self {
+            Self::Relative(interval) => interval.bounds(today),
+            Self::Between { start, end } => Some((start, end)),
+        }
+    }
+
+    /// The start of every `granularity` bucket in the period. An unbounded period spans the days
+    /// of `observed`.
+    ///
+    /// # Errors
+    /// This function returns an error if the period has more than
+    /// [`MAX_POINTS`](Self::MAX_POINTS) buckets, which only an unbounded period can have
+    pub fn buckets(
+        &self,
+        granularity: Granularity,
+        today: NaiveDate,
+        observed: &[NaiveDateTime],
+    ) -> Result<Vec<NaiveDateTime>, Error> {
+        let bounds = self.bounds(today).or_else(|| {
+            let start = observed.iter().min()?.date();
+            let end = observed.iter().max()?.date();
+
+            Some((start, end))
+        });
+
+        let Some((start, end)) = bounds else {
+            return Ok(Vec::new());
+        };
+
+        let end = NaiveDateTime::new(end, NaiveTime::default()) + Duration::days(1);
+        let mut bucket = granularity.truncate(NaiveDateTime::new(start, NaiveTime::default()));
+        let mut buckets = Vec::new();
+
+        while let Some(start) = bucket.filter(|start| *start < end) {
+            if buckets.len() >= usize::try_from(Self::MAX_POINTS).unwrap_or_default() {
+                return Err(Error::new(format!(
+                    "granularity {granularity} splits the period into more than {} data points, \
+                     use a coarser granularity",
+                    Self::MAX_POINTS
+                )));
+            }
+
+            buckets.push(start);
+            bucket = granularity.next(start);
+        }
+
+        Ok(buckets)
+    }
+
+    /// The most days the period covers, or `None` when it is unbounded
+    fn max_days(self) -> Option<i64> {
+        match self {
+            Self::Relative(interval) => interval.max_days(),
+            Self::Between { start, end } => Some((end - start).num_days() + 1),
+        }
+    }
+
+    /// The granularity data points are grouped by when none is requested, chosen so the period
+    /// has a readable number of points
+    #[must_use]
+    pub fn to_granularity(&self) -> Granularity {
+        match self {
+            Self::Relative(interval) => interval.to_granularity(),
+            Self::Between { start, end } => match (*end - *start).num_days() + 1 {
+                ..=2 => Granularity::Hour,
+                3..=62 => Granularity::Day,
+                63..=184 => Granularity::Week,
+                _ => Granularity::Month,
+            },
+        }
     }
 }
 
 impl From<Granularity> for TimeGranularity {
     fn from(input: Granularity) -> Self {
         match input {
-            Granularity::Hour => TimeGranularity::Minute,
-            Granularity::Day => TimeGranularity::Hour,
-            Granularity::Week | Granularity::Month => TimeGranularity::Day,
-            Granularity::Year => TimeGranularity::Month,
+            Granularity::Hour => TimeGranularity::Hour,
+            Granularity::Day => TimeGranularity::Day,
+            Granularity::Week => TimeGranularity::Week,
+            Granularity::Month => TimeGranularity::Month,
+            Granularity::Year => TimeGranularity::Year,
         }
     }
 }
@@ -363,6 +954,30 @@ impl DataPoints {
             .and_then(|s| s.parse().ok())
     }
 
+    /// Helper function to get a field and parse it as u64.
+    fn parse_uint(value: &Value, field: &str) -> Option<u64> {
+        value
+            .get(field)
+            .and_then(Value::as_str)
+            .and_then(|s| s.parse().ok())
+    }
+
+    /// Helper function to get a field as String.
+    fn parse_string(value: &Value, field: &str) -> Option<String> {
+        value
+            .get(field)
+            .and_then(Value::as_str)
+            .map(ToString::to_string)
+    }
+
+    /// Helper function to get a field and parse it as f64.
+    fn parse_float(value: &Value, field: &str) -> Option<f64> {
+        value
+            .get(field)
+            .and_then(Value::as_str)
+            .and_then(|s| s.parse().ok())
+    }
+
     /// Helper function to get a field and parse it as Uuid.
     fn parse_uuid(value: &Value, field: &str) -> Option<Uuid> {
         value
@@ -412,11 +1027,299 @@ impl DataPoints {
 
     fn parse_data(value: &Value, resource: &str) -> Data {
         Data {
-            count: Self::parse_count(value, resource),
-            organization_id: Self::parse_uuid(value, "projects.organization_id"),
+            count: Self::parse_count(value, resource)
+                .or_else(|| Self::parse_uint(value, &format!("{resource}.active"))),
+            organization_id: Self::parse_uuid(value, "projects.organization_id")
+                .or_else(|| Self::parse_uuid(value, &format!("{resource}.organization_id"))),
             project_id: Self::parse_uuid(value, &format!("{resource}.project_id")),
-            collection_id: Self::parse_uuid(value, "mints.collection_id"),
+            collection_id: Self::parse_uuid(value, &format!("{resource}.collection_id")),
+            customer_id: Self::parse_uuid(value, &format!("{resource}.customer_id")),
+            status: Self::parse_string(value, &format!("{resource}.status")),
+            kind: Self::parse_string(value, &format!("{resource}.kind")),
+            blockchain: Self::parse_string(value, &format!("{resource}.blockchain")),
+            sender: Self::parse_string(value, &format!("{resource}.sender")),
+            recipient: Self::parse_string(value, &format!("{resource}.recipient")),
+            supply: Self::parse_uint(value, &format!("{resource}.supply")),
+            remaining_supply: Self::parse_uint(value, &format!("{resource}.remaining_supply")),
+            sell_through: Self::parse_float(value, &format!("{resource}.sell_through")),
+            failure_rate: Self::parse_float(value, &format!("{resource}.failure_rate")),
+            change: None,
+            total: None,
             timestamp: Self::parse_timestamp(value, &format!("{resource}.timestamp")),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use either::Either;
+    use hub_core::chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+
+    use super::{Granularity, Interval, Period, Resource};
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn midnight(y: i32, m: u32, d: u32) -> NaiveDateTime {
+        NaiveDateTime::new(date(y, m, d), NaiveTime::default())
+    }
+
+    #[test]
+    fn intervals_resolve_against_today() {
+        // 2023-09-13 is a Wednesday in the third quarter
+        let today = date(2023, 9, 13);
+        let bounds = |interval: Interval| interval.bounds(today);
+
+        assert_eq!(bounds(Interval::All), None);
+        assert_eq!(bounds(Interval::Today), Some((today, today)));
+        assert_eq!(
+            bounds(Interval::Yesterday),
+            Some((date(2023, 9, 12), date(2023, 9, 12)))
+        );
+        assert_eq!(
+            bounds(Interval::ThisWeek),
+            Some((date(2023, 9, 11), date(2023, 9, 17)))
+        );
+        assert_eq!(
+            bounds(Interval::LastWeek),
+            Some((date(2023, 9, 4), date(2023, 9, 10)))
+        );
+        assert_eq!(bounds(Interval::Last7Days), Some((date(2023, 9, 7), today)));
+        assert_eq!(
+            bounds(Interval::Last30Days),
+            Some((date(2023, 8, 15), today))
+        );
+        assert_eq!(
+            bounds(Interval::ThisMonth),
+            Some((date(2023, 9, 1), date(2023, 9, 30)))
+        );
+        assert_eq!(
+            bounds(Interval::LastMonth),
+            Some((date(2023, 8, 1), date(2023, 8, 31)))
+        );
+        assert_eq!(
+            bounds(Interval::LastQuarter),
+            Some((date(2023, 4, 1), date(2023, 6, 30)))
+        );
+        assert_eq!(
+            bounds(Interval::ThisYear),
+            Some((date(2023, 1, 1), date(2023, 12, 31)))
+        );
+        assert_eq!(
+            bounds(Interval::LastYear),
+            Some((date(2022, 1, 1), date(2022, 12, 31)))
+        );
+    }
+
+    #[test]
+    fn intervals_resolve_across_the_year() {
+        let today = date(2024, 1, 15);
+
+        assert_eq!(
+            Interval::LastMonth.bounds(today),
+            Some((date(2023, 12, 1), date(2023, 12, 31)))
+        );
+        assert_eq!(
+            Interval::LastQuarter.bounds(today),
+            Some((date(2023, 10, 1), date(2023, 12, 31)))
+        );
+        assert_eq!(
+            Interval::ThisMonth.bounds(date(2024, 2, 10)),
+            Some((date(2024, 2, 1), date(2024, 2, 29)))
+        );
+    }
+
+    #[test]
+    fn cube_is_sent_the_resolved_days() {
+        let today = date(2023, 9, 13);
+
+        assert_eq!(
+            Period::Relative(Interval::Last7Days)
+                .resolve(today)
+                .date_range(),
+            Either::Right(vec!["2023-09-07".to_string(), "2023-09-13".to_string()])
+        );
+        assert_eq!(
+            Period::Relative(Interval::All).resolve(today).date_range(),
+            Either::Left("all".to_string())
+        );
+    }
+
+    #[test]
+    fn before_ends_the_day_before_the_period() {
+        let today = date(2023, 9, 13);
+        let epoch = date(1970, 1, 1);
+
+        assert_eq!(
+            Period::Relative(Interval::Last7Days).before(today),
+            Some(Period::Between {
+                start: epoch,
+                end: date(2023, 9, 6)
+            })
+        );
+        assert_eq!(
+            Period::Between {
+                start: date(2023, 9, 1),
+                end: date(2023, 9, 10)
+            }
+            .before(today),
+            Some(Period::Between {
+                start: epoch,
+                end: date(2023, 8, 31)
+            })
+        );
+        assert_eq!(Period::Relative(Interval::All).before(today), None);
+    }
+
+    #[test]
+    fn buckets_cover_the_resolved_days() {
+        let today = date(2023, 9, 13);
+        let buckets = Period::Relative(Interval::Last7Days)
+            .resolve(today)
+            .buckets(Granularity::Day, today, &[])
+            .unwrap();
+
+        assert_eq!(buckets.len(), 7);
+        assert_eq!(buckets.first(), Some(&midnight(2023, 9, 7)));
+        assert_eq!(buckets.last(), Some(&midnight(2023, 9, 13)));
+    }
+
+    #[test]
+    fn unbounded_period_with_too_many_buckets_is_rejected() {
+        let today = date(2023, 9, 13);
+        let observed = [midnight(2020, 1, 1), midnight(2023, 9, 13)];
+        let all = Period::Relative(Interval::All);
+
+        assert!(all.buckets(Granularity::Day, today, &observed).is_err());
+        assert_eq!(
+            all.buckets(Granularity::Month, today, &observed)
+                .unwrap()
+                .len(),
+            45
+        );
+    }
+
+    #[test]
+    fn previous_period_matches_the_elapsed_part() {
+        let today = date(2023, 9, 13);
+
+        assert_eq!(
+            Period::Relative(Interval::ThisMonth).previous(today),
+            Some(Period::Between {
+                start: date(2023, 8, 19),
+                end: date(2023, 8, 31)
+            })
+        );
+        assert_eq!(
+            Period::Relative(Interval::LastMonth).previous(today),
+            Some(Period::Between {
+                start: date(2023, 7, 1),
+                end: date(2023, 7, 31)
+            })
+        );
+        assert_eq!(
+            Period::Between {
+                start: date(2023, 9, 14),
+                end: date(2023, 9, 20)
+            }
+            .previous(today),
+            None
+        );
+        assert_eq!(Period::Relative(Interval::All).previous(today), None);
+    }
+
+    #[test]
+    fn period_extends_back_to_the_bucket_before_its_first() {
+        let today = date(2023, 9, 13);
+
+        assert_eq!(
+            Period::Relative(Interval::ThisMonth).with_previous_bucket(Granularity::Day, today),
+            Some((
+                Period::Between {
+                    start: date(2023, 8, 31),
+                    end: date(2023, 9, 30)
+                },
+                midnight(2023, 9, 1)
+            ))
+        );
+        assert_eq!(
+            Period::Relative(Interval::Last7Days).with_previous_bucket(Granularity::Day, today),
+            Some((
+                Period::Between {
+                    start: date(2023, 9, 6),
+                    end: today
+                },
+                midnight(2023, 9, 7)
+            ))
+        );
+        assert_eq!(
+            Period::Relative(Interval::ThisYear).with_previous_bucket(Granularity::Month, today),
+            Some((
+                Period::Between {
+                    start: date(2022, 12, 1),
+                    end: date(2023, 12, 31)
+                },
+                midnight(2023, 1, 1)
+            ))
+        );
+        // 2023-09-13 is a Wednesday, its week starts on Monday the 11th
+        assert_eq!(
+            Period::Between {
+                start: today,
+                end: today
+            }
+            .with_previous_bucket(Granularity::Week, today),
+            Some((
+                Period::Between {
+                    start: date(2023, 9, 4),
+                    end: today
+                },
+                midnight(2023, 9, 11)
+            ))
+        );
+        assert_eq!(
+            Period::Relative(Interval::All).with_previous_bucket(Granularity::Day, today),
+            None
+        );
+    }
+
+    #[test]
+    fn active_period_ends_today_at_the_latest() {
+        let today = date(2023, 9, 13);
+        let epoch = date(1970, 1, 1);
+
+        assert_eq!(
+            Period::Relative(Interval::ThisMonth).until_end(today),
+            Period::Between {
+                start: epoch,
+                end: today
+            }
+        );
+        assert_eq!(
+            Period::Relative(Interval::LastMonth).until_end(today),
+            Period::Between {
+                start: epoch,
+                end: date(2023, 8, 31)
+            }
+        );
+        assert_eq!(
+            Period::Relative(Interval::All).until_end(today),
+            Period::Between {
+                start: epoch,
+                end: today
+            }
+        );
+    }
+
+    #[test]
+    fn status_is_only_available_on_resources_with_a_status() {
+        assert!(Resource::Mints.supports("status"));
+        assert!(Resource::Collections.supports("status"));
+        assert!(Resource::CollectionHistory.supports("status"));
+        assert!(Resource::Members.supports("status"));
+        assert!(!Resource::Customers.supports("status"));
+        assert!(!Resource::Transfers.supports("status"));
+        assert!(!Resource::Credits.supports("status"));
+    }
+}
@@ -8,8 +8,8 @@ pub use cube_client::models::{
     V1LoadRequestQueryFilterItem, V1LoadRequestQueryTimeDimension, V1LoadResponse,
 };
 pub use datapoint::{
-    DataPoint, DataPoints, DateRange, Dimension, Granularity, Interval, Measure, Operation, Order,
-    Resource, TimeGranularity,
+    Change, CountMode, Data, DataPoint, DataPoints, DateRange, Dimension, Granularity, Group,
+    Interval, Measure, Operation, Order, Period, Resource, TimeGranularity,
 };
 pub use organization::Organization;
 pub use project::Project;
@@ -2,7 +2,7 @@ use async_graphql::{ComplexObject, Context, Result, SimpleObject};
 use hub_core::uuid::Uuid;
 
 use crate::graphql::{
-    objects::{DataPoint, Interval, Order},
+    objects::{CountMode, DataPoint, DateRange, Granularity, Interval, Order},
     queries::analytics::Query,
 };
 
@@ -15,12 +15,17 @@ pub struct Project {
 
 #[ComplexObject]
 impl Project {
+    #[allow(clippy::too_many_arguments)]
     async fn analytics(
         &self,
         ctx: &Context<'_>,
         interval: Option<Interval>,
+        date_range: Option<DateRange>,
+        granularity: Option<Granularity>,
         order: Option<Order>,
         limit: Option<i32>,
+        count: Option<CountMode>,
+        wallet: Option<String>,
     ) -> Result<Vec<DataPoint>> {
         Query::analytics(
             &Query,
@@ -29,8 +34,12 @@ impl Project {
             Some(self.id),
             None,
             interval,
+            date_range,
+            granularity,
             order,
             limit,
+            count,
+            wallet,
         )
         .await
     }
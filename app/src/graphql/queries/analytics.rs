@@ -1,20 +1,23 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 use async_graphql::{Context, Object, Result};
-use either::Either;
 use hub_core::{
-    chrono::{NaiveDate, NaiveDateTime},
+    chrono::{NaiveDate, NaiveDateTime, Utc},
     uuid::Uuid,
 };
 
 use crate::{
     cube_client::{Client, Query as CubeQuery},
     graphql::objects::{
-        DataPoint, DataPoints, Interval, Measure, Operation, Order, Resource, TimeGranularity,
+        Change, CountMode, Data, DataPoint, DataPoints, DateRange, Granularity, Group, Interval,
+        Measure, Operation, Order, Period, Resource, TimeGranularity,
         V1LoadRequestQueryFilterItem as Filter, V1LoadRequestQueryTimeDimension as TimeDimension,
     },
 };
 
+/// Most rows Cube returns for a query, its default `CUBEJS_DB_QUERY_LIMIT`
+const MAX_ROWS: i32 = 50_000;
+
 #[derive(Debug, Clone, Default)]
 pub struct Query;
 
@@ -28,8 +31,14 @@ impl Query {
     /// * `collectionId` - The ID of the collection.
     /// * `measures` - An map array of resources to query (resource, operation).
     /// * `interval` - The timeframe interval. `TODAY` | `YESTERDAY` | `THIS_MONTH` | `LAST_MONTH`
+    /// * `dateRange` - An explicit `start` and `end` date, instead of `interval`.
+    /// * `granularity` - The size of the time buckets. Defaults to one suited to the period.
     /// * `order` - order the results by ASC or DESC.
-    /// * `limit` - Optional limit on the number of data points to retrieve.
+    /// * `limit` - Optional limit on the number of data points to retrieve. Grouped by timestamp,
+    ///   a data point is returned for every bucket of the period, so the limit must cover them
+    ///   all and does not cut the series short.
+    /// * `count` - Count records `CREATED` in the period (default) or `ACTIVE` at its end.
+    /// * `wallet` - Only count transfers sent from or to this wallet address.
     ///
     /// # Returns
     /// A vector of Analytics objects representing the analytics data.
@@ -44,132 +53,516 @@ impl Query {
         project_id: Option<Uuid>,
         collection_id: Option<Uuid>,
         interval: Option<Interval>,
+        date_range: Option<DateRange>,
+        granularity: Option<Granularity>,
         order: Option<Order>,
         limit: Option<i32>,
+        count: Option<CountMode>,
+        wallet: Option<String>,
     ) -> Result<Vec<DataPoint>> {
         let cube = ctx.data::<Client>()?;
         let mut datapoints = Vec::new();
 
-        let selections = Selection::from_context(ctx);
+        let count = count.unwrap_or_default();
+        let selections = Selection::from_context(ctx, count)?;
+        let wallet = wallet_filter(wallet, &selections)?;
+        let today = Utc::now().date_naive();
+        // every query and bucket covers the days the interval spans today
+        let period = Period::from_args(interval, date_range)?.resolve(today);
+        let granularity = period.granularity(granularity)?;
 
         let (id, root) = parse_id_and_root(organization_id, project_id, collection_id)?;
 
         let order = order.unwrap_or(Order::Desc);
+        let mut results = Vec::new();
         let mut use_ts = false;
         for selection in &selections {
             let resource = selection.resource.to_string();
             let ts_dimension = format!("{resource}.timestamp");
-            let mut td = TimeDimension::new(ts_dimension.clone());
-            td.date_range(Either::Left(interval.unwrap_or_default().to_string()));
+            use_ts |= selection.has_ts;
 
-            if selection.has_ts {
-                use_ts = true;
-                td.granularity = Some(interval.unwrap_or_default().to_granularity())
-                    .map(|g| TimeGranularity::from(g).to_string());
+            let filter = root_filter(selection.resource, root, &id);
+
+            let query = |period: Period, granularity: Option<Granularity>, limit: i32| {
+                // a record is active at the end of the period when it was created by then and
+                // was not deleted or deactivated before
+                let (period, active) = match count {
+                    CountMode::Created => (period, None),
+                    CountMode::Active => (
+                        period.until_end(today),
+                        Some(active_at(selection.resource, period.end(today))),
+                    ),
+                };
+
+                let mut td = TimeDimension::new(ts_dimension.clone());
+                td.date_range(period.date_range());
+                td.granularity = granularity.map(|g| TimeGranularity::from(g).to_string());
+
+                let query = CubeQuery::new()
+                    .limit(limit)
+                    .order(&ts_dimension, &order.to_string())
+                    .measures(selection.measures.iter().map(Measure::as_string).collect())
+                    .dimensions(selection.dimensions.clone())
+                    .time_dimensions(Some(td))
+                    .filter_member(filter.clone());
+
+                [wallet.clone(), active]
+                    .into_iter()
+                    .flatten()
+                    .fold(query, CubeQuery::filter_member)
+            };
+
+            // the change of the first bucket is versus the bucket before the period
+            let extended = (selection.has_ts && selection.has_change)
+                .then(|| period.with_previous_bucket(granularity, today))
+                .flatten();
+
+            let window = extended.map_or(period, |(window, _)| window);
+            let mut points = if selection.measures.is_empty() && !selection.snapshot.is_empty() {
+                Vec::new()
+            } else if selection.has_ts {
+                let current = query(window, Some(granularity), MAX_ROWS);
+                load_buckets(cube, current, selection.resource).await?
+            } else {
+                let current = query(window, None, limit.unwrap_or(100));
+                load(cube, current, selection.resource).await?
+            };
+
+            // the supply of a collection is not bound to when it was created, so it is measured
+            // over every collection rather than those created in the period
+            if !selection.snapshot.is_empty() {
+                let snapshot = CubeQuery::new()
+                    .limit(limit.unwrap_or(100))
+                    .measures(selection.snapshot.iter().map(Measure::as_string).collect())
+                    .dimensions(selection.dimensions.clone())
+                    .filter_member(filter.clone());
+
+                let snapshot = load(cube, snapshot, selection.resource).await?;
+                merge_snapshot(&mut points, snapshot, selection.resource);
             }
 
-            let filter = Filter::new()
-                .member(&format!("{resource}.{root}"))
-                .operator("equals")
-                .values(vec![id.clone()]);
-
-            let query = CubeQuery::new()
-                .limit(limit.unwrap_or(100))
-                .order(&ts_dimension, &order.to_string())
-                .measures(selection.measures.iter().map(Measure::as_string).collect())
-                .dimensions(selection.dimensions.clone())
-                .time_dimensions(Some(td.clone()))
-                .filter_member(filter);
-
-            hub_core::tracing::info!("Query: {query:#?}");
-
-            datapoints.extend(
-                DataPoints::from_response(&cube.query(query).await?, selection.resource)?
-                    .into_vec(),
-            );
+            if selection.has_change && !selection.has_ts {
+                if let Some(previous) = period.previous(today) {
+                    let previous = query(previous, None, MAX_ROWS);
+                    let previous = load(cube, previous, selection.resource).await?;
+                    period_changes(&mut points, previous, selection.resource);
+                }
+            }
+
+            // the running totals start from the counts of everything before the first day of the
+            // resolved period, the first day Cube counts in the period
+            let mut before = Vec::new();
+            if selection.has_total {
+                if let Some(period) = period.before(today) {
+                    before = load(cube, query(period, None, MAX_ROWS), selection.resource).await?;
+                }
+            }
+
+            results.push((selection, points, before, extended.map(|(_, first)| first)));
         }
 
-        let dummy_ts: NaiveDateTime = NaiveDate::from_ymd_opt(1900, 1, 1)
-            .unwrap()
-            .and_hms_opt(0, 0, 0)
-            .unwrap();
+        // every resource gets a point in every bucket, so charts have no gaps and all series have
+        // the same length
+        let observed: Vec<_> = results
+            .iter()
+            .flat_map(|(_, points, ..)| points.iter().filter_map(|point| point.timestamp))
+            .collect();
+        let buckets = period.buckets(granularity, today, &observed)?;
 
-        let response = if use_ts {
-            let mut merged: BTreeMap<NaiveDateTime, DataPoint> = BTreeMap::new();
+        if use_ts {
+            check_limit(limit, &buckets)?;
+        }
 
-            for dp in &datapoints {
-                let timestamp = dp.timestamp.unwrap_or(dummy_ts);
-                merged
-                    .entry(timestamp)
-                    .and_modify(|existing_dp| existing_dp.merge(dp))
-                    .or_insert_with(|| dp.clone());
+        for (selection, mut points, before, first) in results {
+            if selection.has_ts {
+                fill_buckets(&mut points, selection.resource, &buckets);
             }
 
-            let mut datapoints: Vec<DataPoint> = merged.into_values().collect();
+            if selection.has_ts && selection.has_change {
+                bucket_changes(&mut points, selection.resource, granularity, first);
+            }
 
-            for dp in &mut datapoints {
-                if dp.timestamp == Some(dummy_ts) {
-                    dp.timestamp = None;
-                }
+            if selection.has_total {
+                running_totals(&mut points, before, selection.resource);
             }
 
-            if matches!(order, Order::Desc) {
-                datapoints.reverse();
+            datapoints.extend(points);
+        }
+
+        Ok(merge(&datapoints, use_ts, order))
+    }
+}
+
+/// Merges the data points of every resource into one point per timestamp, or a single point
+/// when the data is not grouped by time
+fn merge(datapoints: &[DataPoint], use_ts: bool, order: Order) -> Vec<DataPoint> {
+    let dummy_ts: NaiveDateTime = NaiveDate::from_ymd_opt(1900, 1, 1)
+        .unwrap()
+        .and_hms_opt(0, 0, 0)
+        .unwrap();
+
+    if use_ts {
+        let mut merged: BTreeMap<NaiveDateTime, DataPoint> = BTreeMap::new();
+
+        for dp in datapoints {
+            let timestamp = dp.timestamp.unwrap_or(dummy_ts);
+            merged
+                .entry(timestamp)
+                .and_modify(|existing_dp| existing_dp.merge(dp))
+                .or_insert_with(|| dp.clone());
+        }
+
+        let mut datapoints: Vec<DataPoint> = merged.into_values().collect();
+
+        for dp in &mut datapoints {
+            if dp.timestamp == Some(dummy_ts) {
+                dp.timestamp = None;
             }
+        }
 
-            datapoints
-        } else {
-            let mut merged = DataPoint::new();
-            datapoints.iter().for_each(|dp| merged.merge(dp));
-            vec![merged]
-        };
-        Ok(response)
+        if matches!(order, Order::Desc) {
+            datapoints.reverse();
+        }
+
+        datapoints
+    } else {
+        let mut merged = DataPoint::new();
+        datapoints.iter().for_each(|dp| merged.merge(dp));
+        vec![merged]
     }
 }
 
 pub struct Selection {
     pub resource: Resource,
     pub measures: Vec<Measure>,
+    /// Measures of the current state of the records, regardless of the period
+    pub snapshot: Vec<Measure>,
     pub dimensions: Vec<String>,
     pub has_ts: bool,
+    pub has_change: bool,
+    pub has_total: bool,
 }
 
 impl Selection {
-    #[must_use]
-    pub fn from_context(ctx: &Context<'_>) -> Vec<Selection> {
+    /// The resources selected in the query, with the Cube members of their selected fields
+    ///
+    /// # Errors
+    /// This function returns an error if a field is selected on a resource it doesn't apply to
+    pub fn from_context(ctx: &Context<'_>, count: CountMode) -> Result<Vec<Selection>> {
         let mut selections: Vec<Selection> = Vec::new();
 
         for field in ctx.field().selection_set() {
             if let Ok(resource) = field.name().parse::<Resource>() {
                 let mut dimensions = Vec::new();
                 let mut measures = Vec::new();
+                let mut snapshot = Vec::new();
                 let mut has_ts = false;
+                let mut has_change = false;
+                let mut has_total = false;
                 for nested_field in field.selection_set() {
-                    match nested_field.name() {
+                    let name = nested_field.name();
+
+                    if !resource.supports(name) {
+                        return Err(async_graphql::Error::new(format!(
+                            "{name} is not available on {resource}"
+                        )));
+                    }
+
+                    match name {
                         "count" => measures.push(Measure::new(resource, Operation::Count)),
-                        "organizationId" => dimensions.push("projects.organization_id".to_string()),
+                        "failureRate" => {
+                            measures.push(Measure::new(resource, Operation::FailureRate));
+                        },
+                        "remainingSupply" => {
+                            snapshot.push(Measure::new(resource, Operation::RemainingSupply));
+                        },
+                        "sellThrough" => {
+                            snapshot.push(Measure::new(resource, Operation::SellThrough));
+                        },
+                        "organizationId" => dimensions.push(match resource {
+                            Resource::Members
+                            | Resource::Invites
+                            | Resource::Webhooks
+                            | Resource::Credentials => format!("{resource}.organization_id"),
+                            _ => "projects.organization_id".to_string(),
+                        }),
                         "projectId" => dimensions.push(format!("{resource}.project_id")),
                         "collectionId" => dimensions.push(format!("{resource}.collection_id")),
+                        "customerId" => dimensions.push(format!("{resource}.customer_id")),
+                        "status" => dimensions.push(format!("{resource}.status")),
+                        "supply" => dimensions.push(format!("{resource}.supply")),
+                        "kind" => dimensions.push(format!("{resource}.kind")),
+                        "blockchain" => dimensions.push(format!("{resource}.blockchain")),
+                        "sender" => dimensions.push(format!("{resource}.sender")),
+                        "recipient" => dimensions.push(format!("{resource}.recipient")),
                         "timestamp" => has_ts = true,
+                        "change" => has_change = true,
+                        "total" => has_total = true,
                         _ => {},
                     }
                 }
 
+                if count == CountMode::Active && !resource.tracks_activity() {
+                    return Err(async_graphql::Error::new(format!(
+                        "{resource} cannot be counted as ACTIVE"
+                    )));
+                }
+
+                if count == CountMode::Active && has_ts {
+                    return Err(async_graphql::Error::new(
+                        "ACTIVE counts cannot be grouped by timestamp",
+                    ));
+                }
+
+                // an ACTIVE count is already everything up to the end of the period, adding it
+                // to the counts before would count the same records twice
+                if count == CountMode::Active && has_total {
+                    return Err(async_graphql::Error::new(
+                        "total cannot be selected with ACTIVE counts",
+                    ));
+                }
+
+                if has_ts && !snapshot.is_empty() {
+                    return Err(async_graphql::Error::new(
+                        "remainingSupply and sellThrough cannot be grouped by timestamp",
+                    ));
+                }
+
+                // the change and total are computed from the counts, which have to be queried
+                // even when they aren't selected
+                if (has_change || has_total)
+                    && !measures.iter().any(|m| m.operation == Operation::Count)
+                {
+                    measures.push(Measure::new(resource, Operation::Count));
+                }
+
                 let selection = Selection {
                     resource,
                     measures,
+                    snapshot,
                     dimensions,
                     has_ts,
+                    has_change,
+                    has_total,
                 };
 
                 selections.push(selection);
             }
         }
 
-        selections
+        Ok(selections)
+    }
+}
+
+/// Filters the records of `resource` under the `root` organization, project or collection `id`
+fn root_filter(resource: Resource, root: &str, id: &str) -> Filter {
+    // a collection is filtered by its own id rather than a `collection_id` column
+    let member = match (resource, root) {
+        (Resource::Collections, "collection_id") => "collections.id".to_string(),
+        _ => format!("{resource}.{root}"),
+    };
+
+    Filter::new()
+        .member(&member)
+        .operator("equals")
+        .values(vec![id.to_string()])
+}
+
+/// Filters the transfers sent from or to `wallet`
+///
+/// # Errors
+/// This function returns an error if a resource other than transfers is selected
+fn wallet_filter(wallet: Option<String>, selections: &[Selection]) -> Result<Option<Filter>> {
+    if wallet.is_some() && selections.iter().any(|s| s.resource != Resource::Transfers) {
+        return Err(async_graphql::Error::new(
+            "wallet can only filter transfers",
+        ));
+    }
+
+    // a transfer has a row per wallet it involves, its sender and its recipient
+    Ok(wallet.map(|wallet| {
+        Filter::new()
+            .member("transfers.wallet")
+            .operator("equals")
+            .values(vec![wallet])
+    }))
+}
+
+/// Filters the records of `resource` that were neither deleted nor deactivated by the end of `day`
+fn active_at(resource: Resource, day: NaiveDate) -> Filter {
+    Filter::new()
+        .member(&format!("{resource}.active_until"))
+        .operator("afterDate")
+        .values(vec![format!("{}T23:59:59.999", day.format("%Y-%m-%d"))])
+}
+
+/// Runs `query` and parses the data points of `resource` from the response
+async fn load(cube: &Client, query: CubeQuery, resource: Resource) -> Result<Vec<DataPoint>> {
+    hub_core::tracing::info!("Query: {query:#?}");
+
+    Ok(DataPoints::from_response(&cube.query(query).await?, resource)?.into_vec())
+}
+
+/// Runs `query`, grouped by timestamp, and parses the data points of `resource` from the
+/// response. Every bucket of every group is needed to fill and compare the buckets, so a response
+/// cut off at [`MAX_ROWS`] is an error rather than missing buckets read as zeroes.
+///
+/// # Errors
+/// This function returns an error if the response has as many rows as Cube returns at most
+async fn load_buckets(
+    cube: &Client,
+    query: CubeQuery,
+    resource: Resource,
+) -> Result<Vec<DataPoint>> {
+    let points = load(cube, query, resource).await?;
+
+    if points.len() >= MAX_ROWS.unsigned_abs() as usize {
+        return Err(async_graphql::Error::new(
+            "too many data points, use a coarser granularity or a shorter period",
+        ));
+    }
+
+    Ok(points)
+}
+
+/// Checks `limit` leaves room for a data point in each of `buckets`. Queries grouped by
+/// timestamp fetch up to [`MAX_ROWS`] regardless, as every bucket of every group is needed, and
+/// are merged into a point per bucket, so a limit covering the buckets never drops a point.
+///
+/// # Errors
+/// This function returns an error if `limit` is below the number of buckets
+fn check_limit(limit: Option<i32>, buckets: &[NaiveDateTime]) -> Result<()> {
+    match limit.map(usize::try_from) {
+        Some(Ok(limit)) if limit < buckets.len() => Err(async_graphql::Error::new(format!(
+            "limit {limit} is below the {} buckets of the period",
+            buckets.len()
+        ))),
+        Some(Err(_)) => Err(async_graphql::Error::new("limit cannot be negative")),
+        _ => Ok(()),
+    }
+}
+
+/// Sets the supply measures of each group of `points` from `snapshot`, adding a point for the
+/// groups without records in the period.
+fn merge_snapshot(points: &mut Vec<DataPoint>, mut snapshot: Vec<DataPoint>, resource: Resource) {
+    let mut groups: HashMap<Group, Data> = data_of(&mut snapshot, resource)
+        .map(|data| (data.group(), data.clone()))
+        .collect();
+
+    for data in data_of(points, resource) {
+        if let Some(snapshot) = groups.remove(&data.group()) {
+            data.remaining_supply = snapshot.remaining_supply;
+            data.sell_through = snapshot.sell_through;
+        }
+    }
+
+    for data in groups.into_values() {
+        let mut point = DataPoint::new();
+        point.set(resource, &data, None);
+
+        points.push(point);
     }
 }
 
+/// Adds a point with a zero count for each of `buckets` missing from a group of `points`, or from
+/// `points` altogether when there are no records in the period.
+fn fill_buckets(points: &mut Vec<DataPoint>, resource: Resource, buckets: &[NaiveDateTime]) {
+    let mut groups: HashMap<Group, (Data, HashSet<NaiveDateTime>)> = HashMap::new();
+
+    for data in data_of(points, resource) {
+        let (_, seen) = groups
+            .entry(data.group())
+            .or_insert_with(|| (data.clone(), HashSet::new()));
+
+        seen.extend(data.timestamp);
+    }
+
+    if groups.is_empty() {
+        let data = Data::default();
+        groups.insert(data.group(), (data, HashSet::new()));
+    }
+
+    for (data, seen) in groups.into_values() {
+        for bucket in buckets.iter().filter(|bucket| !seen.contains(bucket)) {
+            let mut point = DataPoint::new();
+            point.set(resource, &data.empty(*bucket), Some(*bucket));
+
+            points.push(point);
+        }
+    }
+}
+
+/// Sets the change of each bucket of `points` versus the preceding bucket of its group. Missing
+/// buckets had no records. The buckets before `first`, fetched only to be compared with, are
+/// dropped afterwards.
+fn bucket_changes(
+    points: &mut Vec<DataPoint>,
+    resource: Resource,
+    granularity: Granularity,
+    first: Option<NaiveDateTime>,
+) {
+    let counts: HashMap<_, _> = data_of(points, resource)
+        .filter_map(|data| Some(((data.group(), data.timestamp?), data.count)))
+        .collect();
+
+    for data in data_of(points, resource) {
+        let (Some(timestamp), Some(current)) = (data.timestamp, data.count) else {
+            continue;
+        };
+
+        let previous = granularity
+            .previous(timestamp)
+            .and_then(|previous| counts.get(&(data.group(), previous)).copied().flatten());
+
+        data.change = Some(Change::between(previous.unwrap_or_default(), current));
+    }
+
+    if let Some(first) = first {
+        points.retain(|point| point.timestamp >= Some(first));
+    }
+}
+
+/// Sets the change of each group in `points` versus the same group in `previous`, the totals of
+/// the period before. A group missing from `previous` had no records then.
+fn period_changes(points: &mut [DataPoint], mut previous: Vec<DataPoint>, resource: Resource) {
+    let counts: HashMap<_, _> = data_of(&mut previous, resource)
+        .map(|data| (data.group(), data.count))
+        .collect();
+
+    for data in data_of(points, resource) {
+        let previous = counts.get(&data.group()).copied().flatten();
+
+        data.change = data
+            .count
+            .map(|current| Change::between(previous.unwrap_or_default(), current));
+    }
+}
+
+/// Sets the total of each point of `points` to the count of its group in `before`, the totals
+/// of everything before the period, plus the counts of the group up to and including the point.
+fn running_totals(points: &mut [DataPoint], mut before: Vec<DataPoint>, resource: Resource) {
+    let mut totals: HashMap<_, _> = data_of(&mut before, resource)
+        .map(|data| (data.group(), data.count.unwrap_or_default()))
+        .collect();
+
+    let mut data: Vec<_> = data_of(points, resource).collect();
+    data.sort_by_key(|data| data.timestamp);
+
+    for data in data {
+        let total = totals.entry(data.group()).or_default();
+        *total = total.saturating_add(data.count.unwrap_or_default());
+
+        data.total = Some(*total);
+    }
+}
+
+/// The data of `resource` in every point
+fn data_of(points: &mut [DataPoint], resource: Resource) -> impl Iterator<Item = &mut Data> {
+    points
+        .iter_mut()
+        .flat_map(move |point| point.data_mut(resource).into_iter().flatten())
+}
+
 fn parse_id_and_root(
     organization_id: Option<Uuid>,
     project_id: Option<Uuid>,
@@ -184,3 +577,140 @@ fn parse_id_and_root(
         )),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use either::Either;
+    use hub_core::chrono::{NaiveDate, NaiveDateTime};
+
+    use super::{check_limit, data_of, fill_buckets, merge, running_totals};
+    use crate::graphql::objects::{Data, DataPoint, Interval, Order, Period, Resource};
+
+    fn days(count: u32) -> Vec<NaiveDateTime> {
+        (1..=count)
+            .map(|day| {
+                NaiveDate::from_ymd_opt(2023, 9, day)
+                    .unwrap()
+                    .and_hms_opt(0, 0, 0)
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn limit_covers_every_bucket() {
+        assert!(check_limit(None, &days(30)).is_ok());
+        assert!(check_limit(Some(30), &days(30)).is_ok());
+        assert!(check_limit(Some(29), &days(30)).is_err());
+        assert!(check_limit(Some(-1), &days(30)).is_err());
+    }
+
+    fn point(status: &str, timestamp: NaiveDateTime, count: u64) -> DataPoint {
+        let data = Data {
+            count: Some(count),
+            status: Some(status.to_string()),
+            timestamp: Some(timestamp),
+            ..Data::default()
+        };
+        let mut point = DataPoint::new();
+        point.set(Resource::Mints, &data, Some(timestamp));
+
+        point
+    }
+
+    fn counts(points: &mut [DataPoint]) -> Vec<(Option<String>, NaiveDateTime, Option<u64>)> {
+        let mut counts: Vec<_> = data_of(points, Resource::Mints)
+            .map(|data| (data.status.clone(), data.timestamp.unwrap(), data.count))
+            .collect();
+        counts.sort();
+
+        counts
+    }
+
+    #[test]
+    fn every_group_gets_a_point_in_every_bucket() {
+        let days = days(3);
+        let mut points = vec![point("MINTED", days[0], 3), point("FAILED", days[1], 1)];
+
+        fill_buckets(&mut points, Resource::Mints, &days);
+
+        let minted = Some("MINTED".to_string());
+        let failed = Some("FAILED".to_string());
+        assert_eq!(counts(&mut points), [
+            (failed.clone(), days[0], Some(0)),
+            (failed.clone(), days[1], Some(1)),
+            (failed, days[2], Some(0)),
+            (minted.clone(), days[0], Some(3)),
+            (minted.clone(), days[1], Some(0)),
+            (minted, days[2], Some(0)),
+        ]);
+    }
+
+    #[test]
+    fn buckets_without_records_get_empty_points() {
+        let days = days(2);
+        let mut points = Vec::new();
+
+        fill_buckets(&mut points, Resource::Mints, &days);
+
+        assert_eq!(counts(&mut points), [
+            (None, days[0], Some(0)),
+            (None, days[1], Some(0)),
+        ]);
+    }
+
+    #[test]
+    fn limit_covering_the_buckets_keeps_every_point() {
+        let days = days(30);
+        let mut points = vec![point("MINTED", days[0], 3), point("FAILED", days[0], 1)];
+
+        fill_buckets(&mut points, Resource::Mints, &days);
+        let merged = merge(&points, true, Order::Desc);
+
+        assert!(check_limit(Some(30), &days).is_ok());
+        assert_eq!(merged.len(), days.len());
+    }
+
+    #[test]
+    fn running_totals_start_from_the_day_before_the_period() {
+        let today = NaiveDate::from_ymd_opt(2023, 9, 13).unwrap();
+        let period = Period::Relative(Interval::Last7Days).resolve(today);
+
+        assert_eq!(
+            period.date_range(),
+            Either::Right(vec!["2023-09-07".to_string(), "2023-09-13".to_string()])
+        );
+        assert_eq!(
+            period.before(today),
+            Some(Period::Between {
+                start: NaiveDate::from_ymd_opt(1970, 1, 1).unwrap(),
+                end: NaiveDate::from_ymd_opt(2023, 9, 6).unwrap(),
+            })
+        );
+
+        let days = days(3);
+        let mut points = vec![point("MINTED", days[0], 3), point("MINTED", days[2], 2)];
+        fill_buckets(&mut points, Resource::Mints, &days);
+
+        let before = Data {
+            count: Some(10),
+            status: Some("MINTED".to_string()),
+            ..Data::default()
+        };
+        let mut baseline = DataPoint::new();
+        baseline.set(Resource::Mints, &before, None);
+
+        running_totals(&mut points, vec![baseline], Resource::Mints);
+
+        let mut totals: Vec<_> = data_of(&mut points, Resource::Mints)
+            .map(|data| (data.timestamp.unwrap(), data.total))
+            .collect();
+        totals.sort();
+
+        assert_eq!(totals, [
+            (days[0], Some(13)),
+            (days[1], Some(13)),
+            (days[2], Some(15)),
+        ]);
+    }
+}
@@ -1,3 +1,5 @@
+use std::fmt::Write;
+
 use async_graphql::http::{playground_source, GraphQLPlaygroundConfig};
 use async_graphql_poem::{GraphQLRequest, GraphQLResponse};
 use poem::{
@@ -6,11 +8,53 @@ use poem::{
     IntoResponse, Result,
 };
 
-use crate::{AppContext, AppState, UserID};
+use crate::{
+    events::{self, HandlerMetrics},
+    AppContext, AppState, UserID,
+};
 
 #[handler]
 pub fn health() {}
 
+/// Per handler event processing metrics in the Prometheus text format
+#[handler]
+pub fn metrics() -> String {
+    let counters: [(&str, &str, fn(&HandlerMetrics) -> String); 4] = [
+        ("applied", "Events applied to the projections", |m| {
+            m.applied().to_string()
+        }),
+        (
+            "missing_parent",
+            "Events parked until their parent is recorded",
+            |m| m.missing_parent().to_string(),
+        ),
+        ("failed", "Events that failed to apply", |m| {
+            m.failed().to_string()
+        }),
+        ("seconds", "Time spent handling events", |m| {
+            m.elapsed().as_secs_f64().to_string()
+        }),
+    ];
+    let mut out = String::new();
+
+    for (name, help, value) in counters {
+        writeln!(out, "# HELP hub_analytics_events_{name}_total {help}").ok();
+        writeln!(out, "# TYPE hub_analytics_events_{name}_total counter").ok();
+
+        for (topic, event, metrics) in events::registry().metrics() {
+            let value = value(metrics);
+
+            writeln!(
+                out,
+                "hub_analytics_events_{name}_total{{topic=\"{topic}\",event=\"{event}\"}} {value}"
+            )
+            .ok();
+        }
+    }
+
+    out
+}
+
 #[handler]
 pub fn playground() -> impl IntoResponse {
     Html(playground_source(GraphQLPlaygroundConfig::new("/graphql")))
@@ -0,0 +1,279 @@
+use hub_core::{chrono::Utc, prelude::*};
+use sea_orm::{
+    prelude::*,
+    sea_query::{Expr, OnConflict},
+    Set,
+};
+use serde_json::Value;
+
+use crate::{
+    db::Connection,
+    entities::{consumer_offsets, event_log},
+    Envelope, Metadata,
+};
+
+/// Appends `msg` to the event log as it was received. The key and payload are stored raw so the
+/// message can be processed again, and decoded as JSON so the log can be queried directly.
+/// Messages which can't be decoded are still recorded, without the decoded columns.
+///
+/// # Errors
+/// This function fails if the entry cannot be written
+pub async fn append(db: &Connection, msg: &Envelope) -> Result<()> {
+    let (decoded_key, event, payload) = match msg.decode() {
+        Ok(decoded) => {
+            let (key, payload) = decoded.to_json();
+
+            (
+                Some(key),
+                decoded.event().map(str::to_string),
+                Some(payload),
+            )
+        },
+        Err(_) => (None, None, None),
+    };
+
+    let entry = event_log::ActiveModel {
+        topic: Set(msg.metadata.topic.clone()),
+        partition: Set(msg.metadata.partition),
+        offset: Set(msg.metadata.offset),
+        key: Set(msg.key.clone()),
+        payload: Set(msg.payload.clone()),
+        decoded_key: Set(decoded_key),
+        event: Set(event),
+        decoded_payload: Set(payload),
+        timestamp: Set(msg.metadata.timestamp),
+        received_at: Set(Utc::now().naive_utc()),
+    };
+
+    event_log::Entity::insert(entry)
+        .on_conflict(
+            OnConflict::columns([
+                event_log::Column::Topic,
+                event_log::Column::Partition,
+                event_log::Column::Offset,
+            ])
+            .do_nothing()
+            .to_owned(),
+        )
+        .exec_without_returning(db.get())
+        .await?;
+
+    Ok(())
+}
+
+/// Records that the message at `metadata` was consumed, keeping the first offset consumed from
+/// each partition. A rebuild only truncates the projections if the journal covers every
+/// partition from that offset on, since Kafka may have deleted earlier messages by then.
+///
+/// # Errors
+/// This function fails if the offset cannot be written
+pub async fn consumed(db: &Connection, metadata: &Metadata) -> Result<()> {
+    let start = consumer_offsets::ActiveModel {
+        topic: Set(metadata.topic.clone()),
+        partition: Set(metadata.partition),
+        start_offset: Set(metadata.offset),
+    };
+
+    consumer_offsets::Entity::insert(start)
+        .on_conflict(
+            OnConflict::columns([
+                consumer_offsets::Column::Topic,
+                consumer_offsets::Column::Partition,
+            ])
+            .do_nothing()
+            .to_owned(),
+        )
+        .exec_without_returning(db.get())
+        .await?;
+
+    // workers ordered by key may handle the messages of a partition out of offset order
+    consumer_offsets::Entity::update_many()
+        .col_expr(
+            consumer_offsets::Column::StartOffset,
+            Expr::value(metadata.offset),
+        )
+        .filter(consumer_offsets::Column::Topic.eq(metadata.topic.as_str()))
+        .filter(consumer_offsets::Column::Partition.eq(metadata.partition))
+        .filter(consumer_offsets::Column::StartOffset.gt(metadata.offset))
+        .exec(db.get())
+        .await?;
+
+    Ok(())
+}
+
+impl From<event_log::Model> for Envelope {
+    fn from(entry: event_log::Model) -> Self {
+        let event_log::Model {
+            topic,
+            partition,
+            offset,
+            key,
+            payload,
+            timestamp,
+            ..
+        } = entry;
+
+        Self {
+            metadata: Metadata {
+                topic,
+                partition,
+                offset,
+                timestamp,
+            },
+            key,
+            payload,
+        }
+    }
+}
+
+/// Conversion of a decoded message to the JSON stored in the event log. The protobuf types are
+/// generated by `hub_core_build`, which doesn't derive `Serialize`, so the handler of each topic
+/// lists the fields of its messages with [`messages!`] and [`oneofs!`].
+pub trait ToJson {
+    /// The value as JSON
+    fn to_json(&self) -> Value;
+}
+
+impl ToJson for String {
+    fn to_json(&self) -> Value {
+        Value::from(self.as_str())
+    }
+}
+
+macro_rules! scalars {
+    ($($ty:ty),+ $(,)?) => {
+        $(impl ToJson for $ty {
+            fn to_json(&self) -> Value {
+                Value::from(*self)
+            }
+        })+
+    };
+}
+
+scalars!(bool, i32, i64, u32, u64);
+
+impl<T: ToJson> ToJson for Option<T> {
+    fn to_json(&self) -> Value {
+        self.as_ref().map_or(Value::Null, ToJson::to_json)
+    }
+}
+
+impl<T: ToJson> ToJson for Vec<T> {
+    fn to_json(&self) -> Value {
+        Value::Array(self.iter().map(ToJson::to_json).collect())
+    }
+}
+
+/// Converts messages to objects keyed by field name. Enumerations are stored as their number,
+/// the same as on the wire.
+macro_rules! messages {
+    ($($message:ident { $($field:ident),* $(,)? }),+ $(,)?) => {
+        $(impl $crate::journal::ToJson for $crate::proto::$message {
+            fn to_json(&self) -> ::serde_json::Value {
+                ::serde_json::Value::Object(
+                    [$((stringify!($field).to_string(), $crate::journal::ToJson::to_json(&self.$field))),*]
+                        .into_iter()
+                        .collect(),
+                )
+            }
+        })+
+    };
+}
+
+pub(crate) use messages;
+
+/// Converts the `oneof` of an events message to an object with the case name as its only key.
+/// Cases the service doesn't consume are stored as `null`.
+macro_rules! oneofs {
+    ($($module:ident { $($case:ident),+ $(,)? }),+ $(,)?) => {
+        $(impl $crate::journal::ToJson for $crate::proto::$module::Event {
+            fn to_json(&self) -> ::serde_json::Value {
+                match self {
+                    $(Self::$case(event) => {
+                        ::serde_json::json!({ stringify!($case): $crate::journal::ToJson::to_json(event) })
+                    },)+
+                    #[allow(unreachable_patterns)]
+                    _ => ::serde_json::Value::Null,
+                }
+            }
+        })+
+    };
+}
+
+pub(crate) use oneofs;
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::ToJson;
+    use crate::{
+        events::{registry, test::*},
+        proto::{
+            credits_event, nft_events, Creator, MasterEdition, MetaplexMasterEditionTransaction,
+        },
+    };
+
+    const CREDIT: &str = "7eb0a199-d034-41e2-9264-c4d16083ef76";
+
+    #[test]
+    fn event_is_keyed_by_its_case() {
+        let decoded = registry().decode(&purchase(CREDIT, 0)).unwrap();
+        let (key, payload) = decoded.to_json();
+
+        assert_eq!(decoded.event(), Some("CreditsPurchased"));
+        assert_eq!(key, json!({ "id": CREDIT, "user_id": "" }));
+        assert_eq!(
+            payload,
+            json!({
+                "event": {
+                    "CreditsPurchased": {
+                        "organization_id": ORGANIZATION,
+                        "amount": 100,
+                    },
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn nested_messages_keep_their_fields() {
+        let event = nft_events::Event::SolanaCreateDrop(MetaplexMasterEditionTransaction {
+            master_edition: Some(MasterEdition {
+                name: "Launch".to_string(),
+                seller_fee_basis_points: 500,
+                supply: None,
+                creators: vec![Creator {
+                    address: "creator".to_string(),
+                    verified: true,
+                    share: 100,
+                }],
+                ..MasterEdition::default()
+            }),
+        });
+
+        assert_eq!(
+            event.to_json(),
+            json!({
+                "SolanaCreateDrop": {
+                    "master_edition": {
+                        "name": "Launch",
+                        "symbol": "",
+                        "metadata_uri": "",
+                        "seller_fee_basis_points": 500,
+                        "supply": null,
+                        "creators": [{ "address": "creator", "verified": true, "share": 100 }],
+                        "owner_address": "",
+                    },
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn unconsumed_case_is_null() {
+        let event = credits_event::Event::Other(String::new());
+
+        assert_eq!(event.to_json(), json!(null));
+    }
+}
@@ -4,13 +4,24 @@
 
 pub mod cube_client;
 pub mod db;
+pub mod dead_letters;
 #[allow(clippy::pedantic)]
 pub mod entities;
 pub mod events;
 pub mod graphql;
 pub mod handlers;
+pub mod journal;
+pub mod pending;
+pub mod processor;
+pub mod rebuild;
 use db::Connection;
-use hub_core::{clap, consumer::RecvError, prelude::*, uuid::Uuid};
+use hub_core::{
+    chrono::{NaiveDateTime, TimeZone, Utc},
+    clap,
+    consumer::RecvError,
+    prelude::*,
+    uuid::Uuid,
+};
 use poem::{async_trait, FromRequest, Request, RequestBody};
 #[allow(clippy::pedantic)]
 pub mod proto {
@@ -18,75 +29,82 @@ pub mod proto {
     include!(concat!(env!("OUT_DIR"), "/customer.proto.rs"));
     include!(concat!(env!("OUT_DIR"), "/treasury.proto.rs"));
     include!(concat!(env!("OUT_DIR"), "/credential.proto.rs"));
+    include!(concat!(env!("OUT_DIR"), "/credits.proto.rs"));
+    include!(concat!(env!("OUT_DIR"), "/credits_mpsc.proto.rs"));
     include!(concat!(env!("OUT_DIR"), "/webhook.proto.rs"));
     include!(concat!(env!("OUT_DIR"), "/nfts.proto.rs"));
     include!(concat!(env!("OUT_DIR"), "/solana_nfts.proto.rs"));
     include!(concat!(env!("OUT_DIR"), "/polygon_nfts.proto.rs"));
 }
 
-#[derive(Debug)]
-pub enum Services {
-    Organizations(proto::OrganizationEventKey, proto::OrganizationEvents),
-    Customers(proto::CustomerEventKey, proto::CustomerEvents),
-    Treasuries(proto::TreasuryEventKey, proto::TreasuryEvents),
-    Webhooks(proto::WebhookEventKey, proto::WebhookEvents),
-    Nfts(proto::NftEventKey, proto::NftEvents),
-    SolanaNfts(proto::SolanaNftEventKey, proto::SolanaNftEvents),
+/// A hub message read from Kafka. The payload is kept raw and decoded when it is processed so
+/// that a message which fails either step can be stored as a dead letter and retried later.
+#[derive(Debug, Clone)]
+pub struct Envelope {
+    pub metadata: Metadata,
+    pub key: Vec<u8>,
+    pub payload: Vec<u8>,
 }
 
-impl hub_core::consumer::MessageGroup for Services {
-    const REQUESTED_TOPICS: &'static [&'static str] = &[
-        "hub-orgs",
-        "hub-customers",
-        "hub-treasuries",
-        "hub-webhooks",
-        "hub-credits",
-        "hub-nfts",
-        "hub-nfts-solana",
-        "hub-nfts-polygon",
-    ];
+/// Kafka message metadata captured when an event is received
+#[derive(Debug, Clone)]
+pub struct Metadata {
+    pub topic: String,
+    pub partition: i32,
+    pub offset: i64,
+    /// When the event happened, used for every analytics timestamp
+    pub timestamp: NaiveDateTime,
+}
+
+impl Metadata {
+    /// Hub payloads don't carry their own timestamp, so the event time is resolved in order from
+    /// the Kafka message timestamp (`CreateTime` set by the producer, or `LogAppendTime` for topics
+    /// configured with broker time) and only then the time the message was consumed.
+    fn from_message<M: hub_core::consumer::Message>(msg: &M) -> Self {
+        let timestamp = msg
+            .timestamp()
+            .to_millis()
+            .and_then(|millis| Utc.timestamp_millis_opt(millis).single())
+            .unwrap_or_else(Utc::now)
+            .naive_utc();
+
+        Self {
+            topic: msg.topic().to_string(),
+            partition: msg.partition(),
+            offset: msg.offset(),
+            timestamp,
+        }
+    }
+}
+
+impl hub_core::consumer::MessageGroup for Envelope {
+    const REQUESTED_TOPICS: &'static [&'static str] = events::TOPICS;
 
     fn from_message<M: hub_core::consumer::Message>(msg: &M) -> Result<Self, RecvError> {
-        let topic = msg.topic();
+        let metadata = Metadata::from_message(msg);
         let key = msg.key().ok_or(RecvError::MissingKey)?;
-        let val = msg.payload().ok_or(RecvError::MissingPayload)?;
+        let payload = msg.payload().ok_or(RecvError::MissingPayload)?;
+
+        Ok(Self {
+            metadata,
+            key: key.to_vec(),
+            payload: payload.to_vec(),
+        })
+    }
+}
+
+impl Envelope {
+    /// Decodes the key and payload with the handler registered for the topic
+    ///
+    /// # Errors
+    /// This function fails if the topic is unknown or the message is not valid protobuf
+    pub fn decode(&self) -> Result<Box<dyn events::Decoded>, RecvError> {
+        let topic = self.metadata.topic.as_str();
+        let key = self.key.as_slice();
+        let val = self.payload.as_slice();
         info!(topic, ?key, ?val);
 
-        match topic {
-            "hub-orgs" => {
-                let key = proto::OrganizationEventKey::decode(key)?;
-                let val = proto::OrganizationEvents::decode(val)?;
-
-                Ok(Services::Organizations(key, val))
-            },
-            "hub-customers" => {
-                let key = proto::CustomerEventKey::decode(key)?;
-                let val = proto::CustomerEvents::decode(val)?;
-
-                Ok(Services::Customers(key, val))
-            },
-            "hub-treasuries" => {
-                let key = proto::TreasuryEventKey::decode(key)?;
-                let val = proto::TreasuryEvents::decode(val)?;
-
-                Ok(Services::Treasuries(key, val))
-            },
-            "hub-webhooks" => todo!(),
-            "hub-credits" => todo!(),
-            "hub-nfts" => {
-                let key = proto::NftEventKey::decode(key)?;
-                let val = proto::NftEvents::decode(val)?;
-
-                Ok(Services::Nfts(key, val))
-            },
-            "hub-nfts-solana" => {
-                let key = proto::SolanaNftEventKey::decode(key)?;
-                let val = proto::SolanaNftEvents::decode(val)?;
-
-                Ok(Services::SolanaNfts(key, val))
-            },
-            t => Err(RecvError::BadTopic(t.into())),
-        }
+        events::registry().decode(self)
     }
 }
 
@@ -101,6 +119,24 @@ pub struct Args {
 
     #[command(flatten)]
     pub cube: cube_client::CubeArgs,
+
+    #[command(flatten)]
+    pub processor: processor::ProcessorArgs,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum Command {
+    /// Retry the oldest messages recorded in the dead letter table, then exit
+    RetryDeadLetters {
+        /// Maximum number of dead letters to retry
+        #[arg(long, default_value_t = 100)]
+        limit: u64,
+    },
+    /// Rebuild the analytics tables by replaying the event journal, then exit
+    Rebuild(rebuild::RebuildArgs),
 }
 
 #[derive(Debug, Clone, Copy)]
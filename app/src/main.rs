@@ -1,15 +1,13 @@
 use holaplex_hub_analytics::{
     cube_client::Client,
     db::Connection,
-    events,
+    dead_letters,
     graphql::schema::build_schema,
-    handlers::{graphql_handler, health, playground},
-    AppState, Args, Services,
-};
-use hub_core::{
-    prelude::*,
-    tokio::{self, task},
+    handlers::{graphql_handler, health, metrics, playground},
+    processor::Processor,
+    rebuild, AppState, Args, Command, Envelope,
 };
+use hub_core::{prelude::*, tokio};
 use poem::{get, listener::TcpListener, middleware::AddData, post, EndpointExt, Route, Server};
 
 pub fn main() {
@@ -18,31 +16,66 @@ pub fn main() {
     };
 
     hub_core::run(opts, |common, args| {
-        let Args { port, db, cube } = args;
+        let Args {
+            port,
+            db,
+            cube,
+            processor,
+            command,
+        } = args;
 
         common.rt.block_on(async move {
             let connection = Connection::new(db)
                 .await
                 .context("failed to get database connection")?;
 
+            match command {
+                Some(Command::RetryDeadLetters { limit }) => {
+                    let retried = dead_letters::retry(&connection, limit).await?;
+                    info!(
+                        succeeded = retried.succeeded,
+                        parked = retried.parked,
+                        failed = retried.failed,
+                        "dead letter retry finished"
+                    );
+
+                    return Ok(());
+                },
+                Some(Command::Rebuild(args)) => {
+                    let rebuilt = rebuild::run(&connection, args).await?;
+                    info!(
+                        truncated = rebuilt.truncated,
+                        cleared = rebuilt.cleared,
+                        replayed = rebuilt.replayed,
+                        skipped = rebuilt.skipped,
+                        failed = rebuilt.failed,
+                        discrepancies = rebuilt.discrepancies.len(),
+                        "rebuild finished"
+                    );
+
+                    return Ok(());
+                },
+                None => (),
+            }
+
             let schema = build_schema();
             let cube_client = Client::from_args(&cube)?;
             let state = AppState::new(schema, connection.clone(), cube_client.clone());
-            let cons = common.consumer_cfg.build::<Services>().await?;
+            let cons = common.consumer_cfg.build::<Envelope>().await?;
+
+            let processor = Processor::new(&processor, &connection);
 
             tokio::spawn(async move {
                 {
                     let mut stream = cons.stream();
                     loop {
-                        let connection = connection.clone();
                         match stream.next().await {
                             Some(Ok(msg)) => {
                                 info!(?msg, "message received");
 
-                                tokio::spawn(async move {
-                                    events::process(msg, connection.clone()).await
-                                });
-                                task::yield_now().await;
+                                if let Err(e) = processor.send(msg).await {
+                                    error!("failed to queue message: {e:#}");
+                                }
                             },
                             None => (),
                             Some(Err(e)) => {
@@ -58,7 +91,8 @@ pub fn main() {
                     Route::new()
                         .at("/graphql", post(graphql_handler).with(AddData::new(state)))
                         .at("/playground", get(playground))
-                        .at("/health", get(health)),
+                        .at("/health", get(health))
+                        .at("/metrics", get(metrics)),
                 )
                 .await
                 .context("failed to build graphql server")
@@ -0,0 +1,336 @@
+use std::collections::HashSet;
+
+use hub_core::{
+    chrono::{Duration, Utc},
+    prelude::*,
+};
+use sea_orm::{prelude::*, sea_query::OnConflict, Condition, QueryOrder, QuerySelect, Set};
+
+use crate::{
+    db::Connection,
+    dead_letters,
+    entities::pending_events,
+    events::{self, MissingParent},
+    Envelope, Metadata,
+};
+
+/// Outcome of a [`sweep`]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Swept {
+    pub applied: u64,
+    pub waiting: u64,
+    pub expired: u64,
+    pub failed: u64,
+}
+
+/// Parks `msg` until the parent it is missing has been recorded. Parking a message again only
+/// updates the parent it is waiting on.
+///
+/// # Errors
+/// This function fails if the pending event cannot be written
+pub async fn park(db: &Connection, msg: &Envelope, missing: &MissingParent) -> Result<()> {
+    let Metadata {
+        topic,
+        partition,
+        offset,
+        timestamp,
+    } = msg.metadata.clone();
+
+    let model = pending_events::ActiveModel {
+        topic: Set(topic),
+        partition: Set(partition),
+        offset: Set(offset),
+        key: Set(msg.key.clone()),
+        payload: Set(msg.payload.clone()),
+        parent_table: Set(missing.table.clone()),
+        parent_id: Set(missing.id),
+        timestamp: Set(timestamp),
+        created_at: Set(Utc::now().naive_utc()),
+    };
+
+    pending_events::Entity::insert(model)
+        .on_conflict(
+            OnConflict::columns([
+                pending_events::Column::Topic,
+                pending_events::Column::Partition,
+                pending_events::Column::Offset,
+            ])
+            .update_columns([
+                pending_events::Column::ParentTable,
+                pending_events::Column::ParentId,
+            ])
+            .to_owned(),
+        )
+        .exec_without_returning(db.get())
+        .await?;
+
+    Ok(())
+}
+
+/// The parent the earliest parked message with the same topic and key as `msg` is waiting on.
+/// Messages of a key are applied in order, so while one of them is parked the later ones must be
+/// parked behind it.
+///
+/// # Errors
+/// This function fails if the pending events cannot be read
+pub async fn blocking(db: &Connection, msg: &Envelope) -> Result<Option<MissingParent>> {
+    let parked = pending_events::Entity::find()
+        .filter(pending_events::Column::Topic.eq(msg.metadata.topic.as_str()))
+        .filter(pending_events::Column::Key.eq(msg.key.clone()))
+        .order_by_asc(pending_events::Column::Partition)
+        .order_by_asc(pending_events::Column::Offset)
+        .one(db.get())
+        .await?;
+
+    Ok(parked.map(|event| MissingParent {
+        table: event.parent_table,
+        id: event.parent_id,
+    }))
+}
+
+/// Retries every parked message through [`events::process`], reading them `page_size` at a time
+/// in offset order. Messages whose parent has arrived are applied and removed. Messages still
+/// missing a parent after `timeout`, or failing for any other reason, are moved to the dead
+/// letter table. Once a message of a key stays parked, the later messages of that key are left
+/// parked so they are still applied in order.
+///
+/// # Errors
+/// This function fails if the pending events cannot be read or moved
+pub async fn sweep(db: &Connection, timeout: Duration, page_size: u64) -> Result<Swept> {
+    let expires = Utc::now().naive_utc() - timeout;
+    let mut blocked = HashSet::new();
+    let mut after = None;
+    let mut swept = Swept::default();
+
+    loop {
+        let parked = page(db, after.take(), page_size).await?;
+
+        let Some(last) = parked.last() else {
+            break;
+        };
+        after = Some((last.topic.clone(), last.partition, last.offset));
+
+        for event in parked {
+            let key = (event.topic.clone(), event.key.clone());
+
+            if blocked.contains(&key) {
+                swept.waiting += 1;
+                continue;
+            }
+
+            if !apply(db, event, expires, timeout, &mut swept).await? {
+                blocked.insert(key);
+            }
+        }
+    }
+
+    Ok(swept)
+}
+
+/// The parked messages following `after` in offset order
+async fn page(
+    db: &Connection,
+    after: Option<(String, i32, i64)>,
+    page_size: u64,
+) -> Result<Vec<pending_events::Model>> {
+    let mut query = pending_events::Entity::find()
+        .order_by_asc(pending_events::Column::Topic)
+        .order_by_asc(pending_events::Column::Partition)
+        .order_by_asc(pending_events::Column::Offset)
+        .limit(page_size);
+
+    if let Some((topic, partition, offset)) = after {
+        query = query.filter(
+            Condition::any()
+                .add(pending_events::Column::Topic.gt(topic.as_str()))
+                .add(
+                    Condition::all()
+                        .add(pending_events::Column::Topic.eq(topic.as_str()))
+                        .add(pending_events::Column::Partition.gt(partition)),
+                )
+                .add(
+                    Condition::all()
+                        .add(pending_events::Column::Topic.eq(topic))
+                        .add(pending_events::Column::Partition.eq(partition))
+                        .add(pending_events::Column::Offset.gt(offset)),
+                ),
+        );
+    }
+
+    Ok(query.all(db.get()).await?)
+}
+
+/// Retries one parked message. Returns `false` if it is still waiting on its parent.
+async fn apply(
+    db: &Connection,
+    event: pending_events::Model,
+    expires: DateTime,
+    timeout: Duration,
+    swept: &mut Swept,
+) -> Result<bool> {
+    let created_at = event.created_at;
+    let msg = Envelope::from(event.clone());
+
+    match events::process(&msg, db.clone()).await {
+        Ok(()) => swept.applied += 1,
+        Err(e) => match e.downcast_ref::<MissingParent>() {
+            Some(missing) if created_at > expires => {
+                park(db, &msg, missing).await?;
+                swept.waiting += 1;
+
+                return Ok(false);
+            },
+            Some(missing) => {
+                warn!(
+                    topic = msg.metadata.topic,
+                    offset = msg.metadata.offset,
+                    "{missing} after {}s, moving to dead letters",
+                    timeout.num_seconds()
+                );
+
+                let e = e.context("parent was not recorded before the pending timeout");
+                dead_letters::record(db, &msg, &e).await?;
+                swept.expired += 1;
+            },
+            None => {
+                dead_letters::record(db, &msg, &e).await?;
+                swept.failed += 1;
+            },
+        },
+    }
+
+    event.delete(db.get()).await?;
+
+    Ok(true)
+}
+
+impl From<pending_events::Model> for Envelope {
+    fn from(event: pending_events::Model) -> Self {
+        let pending_events::Model {
+            topic,
+            partition,
+            offset,
+            key,
+            payload,
+            timestamp,
+            ..
+        } = event;
+
+        Self {
+            metadata: Metadata {
+                topic,
+                partition,
+                offset,
+                timestamp,
+            },
+            key,
+            payload,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hub_core::{
+        chrono::{Duration, Utc},
+        tokio,
+    };
+    use sea_orm::{prelude::*, MockExecResult};
+
+    use super::sweep;
+    use crate::{
+        db::Connection,
+        entities::{dead_letters, organizations, pending_events},
+        events::test::*,
+    };
+
+    const CREDIT: &str = "7eb0a199-d034-41e2-9264-c4d16083ef76";
+    const OTHER_CREDIT: &str = "8fc1b2aa-e145-42f3-a375-d5e27194f087";
+
+    /// The purchase of `credit` at `offset`, parked waiting on its organization since `created_at`
+    fn parked(credit: &str, offset: i64, created_at: DateTime) -> pending_events::Model {
+        let msg = purchase(credit, offset);
+
+        pending_events::Model {
+            topic: msg.metadata.topic,
+            partition: msg.metadata.partition,
+            offset,
+            key: msg.key,
+            payload: msg.payload,
+            parent_table: "organizations".to_string(),
+            parent_id: ORGANIZATION.parse().unwrap(),
+            timestamp: msg.metadata.timestamp,
+            created_at,
+        }
+    }
+
+    #[tokio::test(crate = "hub_core::tokio")]
+    async fn later_messages_of_a_waiting_key_stay_parked() {
+        let now = Utc::now().naive_utc();
+        let db: Connection = mock()
+            .append_query_results([vec![
+                parked(CREDIT, 0, now),
+                parked(CREDIT, 1, now),
+                parked(OTHER_CREDIT, 2, now),
+            ]])
+            .append_query_results([Vec::<organizations::Model>::new()])
+            .append_exec_results([MockExecResult::default()])
+            .append_query_results([[organization()]])
+            .append_exec_results([MockExecResult::default(), MockExecResult::default()])
+            .append_query_results([Vec::<pending_events::Model>::new()])
+            .into_connection()
+            .into();
+
+        let swept = sweep(&db, Duration::hours(1), 500).await.unwrap();
+
+        assert_eq!(
+            (swept.applied, swept.waiting, swept.expired, swept.failed),
+            (1, 2, 0, 0)
+        );
+
+        let log = db.into_inner().unwrap().into_transaction_log();
+        assert!(ran(&log, OTHER_CREDIT));
+        assert!(!ran(
+            &log,
+            r#"INSERT INTO "credits" ("id", "organization_id", "amount", "action", "timestamp") VALUES ($1, $2, $3, $4, $5) ON CONFLICT ("id") DO NOTHING", values: [Uuid(Some(7eb0a199"#
+        ));
+    }
+
+    #[tokio::test(crate = "hub_core::tokio")]
+    async fn message_waiting_past_the_timeout_is_dead_lettered() {
+        let now = Utc::now().naive_utc();
+        let msg = parked(CREDIT, 0, now - Duration::hours(2));
+        let letter = dead_letters::Model {
+            topic: msg.topic.clone(),
+            partition: msg.partition,
+            offset: msg.offset,
+            key: msg.key.clone(),
+            payload: msg.payload.clone(),
+            error: String::new(),
+            attempts: 1,
+            timestamp: msg.timestamp,
+            created_at: now,
+            updated_at: now,
+        };
+        let db: Connection = mock()
+            .append_query_results([[msg]])
+            .append_query_results([Vec::<organizations::Model>::new()])
+            .append_query_results([Vec::<dead_letters::Model>::new()])
+            .append_query_results([[letter]])
+            .append_exec_results([MockExecResult::default()])
+            .append_query_results([Vec::<pending_events::Model>::new()])
+            .into_connection()
+            .into();
+
+        let swept = sweep(&db, Duration::hours(1), 500).await.unwrap();
+
+        assert_eq!(
+            (swept.applied, swept.waiting, swept.expired, swept.failed),
+            (0, 0, 1, 0)
+        );
+
+        let log = db.into_inner().unwrap().into_transaction_log();
+        assert!(ran(&log, r#"INSERT INTO "dead_letters""#));
+        assert!(ran(&log, r#"DELETE FROM "pending_events""#));
+    }
+}
@@ -0,0 +1,339 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    time::Duration,
+};
+
+use hub_core::{
+    chrono, clap,
+    prelude::*,
+    tokio::{self, sync::mpsc, time},
+};
+
+use crate::{
+    db::Connection,
+    dead_letters,
+    events::{self, MissingParent},
+    journal, pending, Envelope,
+};
+
+/// Arguments for the event processing pipeline
+#[derive(Debug, clap::Args)]
+pub struct ProcessorArgs {
+    /// Number of workers processing messages concurrently
+    #[arg(long, env, default_value_t = 16)]
+    pub processor_workers: usize,
+    /// Number of messages queued per worker before the consumer stops reading from Kafka
+    #[arg(long, env, default_value_t = 64)]
+    pub processor_queue_size: usize,
+    /// Which messages must be processed in the order they were received
+    #[arg(long, env, value_enum, default_value_t = Ordering::Partition)]
+    pub processor_ordering: Ordering,
+    /// Seconds between retries of messages parked while their parent is missing
+    #[arg(long, env, default_value_t = 5)]
+    pub processor_pending_interval: u32,
+    /// Seconds a parked message waits for its parent before it is moved to the dead letters
+    #[arg(long, env, default_value_t = 3600)]
+    pub processor_pending_timeout: u32,
+}
+
+/// Messages sharing an ordering key are always handled by the same worker, one at a time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Ordering {
+    /// Preserve the order of every message within a topic partition
+    Partition,
+    /// Preserve the order of messages with the same Kafka key
+    Key,
+}
+
+/// Fans messages out to a fixed set of workers over bounded queues
+#[derive(Debug, Clone)]
+pub struct Processor {
+    workers: Vec<mpsc::Sender<Envelope>>,
+    ordering: Ordering,
+}
+
+impl Processor {
+    /// Spawns the workers, each writing to the database through `db`, and the task sweeping
+    /// parked messages
+    #[must_use]
+    pub fn new(args: &ProcessorArgs, db: &Connection) -> Self {
+        let ProcessorArgs {
+            processor_workers,
+            processor_queue_size,
+            processor_ordering,
+            processor_pending_interval,
+            processor_pending_timeout,
+        } = *args;
+
+        tokio::spawn(sweep(
+            db.clone(),
+            Duration::from_secs(processor_pending_interval.max(1).into()),
+            chrono::Duration::seconds(processor_pending_timeout.into()),
+        ));
+
+        let workers = (0..processor_workers.max(1))
+            .map(|_| {
+                let (tx, rx) = mpsc::channel(processor_queue_size.max(1));
+                tokio::spawn(work(rx, db.clone()));
+                tx
+            })
+            .collect();
+
+        Self {
+            workers,
+            ordering: processor_ordering,
+        }
+    }
+
+    /// Queues `msg` on the worker owning its ordering key, waiting while that worker's queue is
+    /// full so the consumer applies backpressure to the Kafka stream.
+    ///
+    /// # Errors
+    /// This function fails if the worker has stopped
+    pub async fn send(&self, msg: Envelope) -> Result<()> {
+        let mut hasher = DefaultHasher::new();
+
+        match self.ordering {
+            Ordering::Partition => {
+                msg.metadata.topic.hash(&mut hasher);
+                msg.metadata.partition.hash(&mut hasher);
+            },
+            Ordering::Key => msg.key.hash(&mut hasher),
+        }
+
+        let worker = usize::try_from(hasher.finish() % self.workers.len() as u64)?;
+
+        self.workers[worker]
+            .send(msg)
+            .await
+            .map_err(|_| anyhow!("processor worker {worker} stopped"))
+    }
+}
+
+async fn work(mut rx: mpsc::Receiver<Envelope>, db: Connection) {
+    while let Some(msg) = rx.recv().await {
+        if let Err(e) = handle(&msg, &db).await {
+            error!(
+                topic = msg.metadata.topic,
+                offset = msg.metadata.offset,
+                "failed to handle message: {e:#}"
+            );
+        }
+    }
+}
+
+/// Journals and processes `msg`, parking it when a parent is missing or an earlier message of its
+/// key is parked, and dead lettering any other failure, including a failure to journal it
+async fn handle(msg: &Envelope, db: &Connection) -> Result<()> {
+    // a message missing from the journal would be lost by a rebuild, so it is only processed
+    // once it has been journaled
+    let journaled = match journal::consumed(db, &msg.metadata).await {
+        Ok(()) => journal::append(db, msg).await,
+        Err(e) => Err(e),
+    };
+
+    if let Err(e) = journaled {
+        let e = e.context("failed to journal message");
+        warn!(
+            topic = msg.metadata.topic,
+            offset = msg.metadata.offset,
+            "{e:#}"
+        );
+
+        return dead_letters::record(db, msg, &e).await;
+    }
+
+    if let Some(missing) = pending::blocking(db, msg).await? {
+        info!(
+            topic = msg.metadata.topic,
+            offset = msg.metadata.offset,
+            "parking message behind an earlier message of its key: {missing}"
+        );
+
+        return pending::park(db, msg, &missing).await;
+    }
+
+    let Err(e) = events::process(msg, db.clone()).await else {
+        return Ok(());
+    };
+
+    if let Some(missing) = e.downcast_ref::<MissingParent>() {
+        info!(
+            topic = msg.metadata.topic,
+            offset = msg.metadata.offset,
+            "parking message: {missing}"
+        );
+
+        return pending::park(db, msg, missing).await;
+    }
+
+    warn!(
+        topic = msg.metadata.topic,
+        offset = msg.metadata.offset,
+        "failed to process message: {e:#}"
+    );
+
+    dead_letters::record(db, msg, &e).await
+}
+
+async fn sweep(db: Connection, interval: Duration, timeout: chrono::Duration) {
+    let mut interval = time::interval(interval);
+
+    loop {
+        interval.tick().await;
+
+        match pending::sweep(&db, timeout, 500).await {
+            Ok(swept) if swept.applied + swept.expired + swept.failed > 0 => info!(
+                applied = swept.applied,
+                waiting = swept.waiting,
+                expired = swept.expired,
+                failed = swept.failed,
+                "swept pending messages"
+            ),
+            Ok(_) => (),
+            Err(e) => error!("failed to sweep pending messages: {e:#}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hub_core::tokio::{self, sync::mpsc};
+    use sea_orm::MockExecResult;
+
+    use super::{handle, Ordering, Processor};
+    use crate::{
+        db::Connection,
+        entities::{organizations, pending_events},
+        events::test::*,
+        Envelope,
+    };
+
+    const CREDIT: &str = "7eb0a199-d034-41e2-9264-c4d16083ef76";
+
+    /// A processor with `workers` queues, along with the receiving end of each queue
+    fn processor(ordering: Ordering, workers: usize) -> (Processor, Vec<mpsc::Receiver<Envelope>>) {
+        let (workers, queues) = (0..workers).map(|_| mpsc::channel(64)).unzip();
+
+        (Processor { workers, ordering }, queues)
+    }
+
+    /// The messages queued on each worker, as the partition and offset of each message
+    fn queued(queues: &mut [mpsc::Receiver<Envelope>]) -> Vec<Vec<(i32, i64)>> {
+        queues
+            .iter_mut()
+            .map(|queue| {
+                std::iter::from_fn(|| queue.try_recv().ok())
+                    .map(|msg| (msg.metadata.partition, msg.metadata.offset))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[tokio::test(crate = "hub_core::tokio")]
+    async fn partition_is_handled_by_one_worker_in_order() {
+        let (processor, mut queues) = processor(Ordering::Partition, 4);
+
+        for offset in 0..4 {
+            for partition in 0..8 {
+                let mut msg = purchase(&format!("{offset}"), offset);
+                msg.metadata.partition = partition;
+
+                processor.send(msg).await.unwrap();
+            }
+        }
+
+        let queued = queued(&mut queues);
+        assert!(queued.iter().filter(|queued| !queued.is_empty()).count() > 1);
+
+        for partition in 0..8 {
+            let workers: Vec<_> = queued
+                .iter()
+                .filter_map(|queued| {
+                    let offsets: Vec<_> = queued
+                        .iter()
+                        .filter(|(p, _)| *p == partition)
+                        .map(|(_, offset)| *offset)
+                        .collect();
+
+                    (!offsets.is_empty()).then_some(offsets)
+                })
+                .collect();
+
+            assert_eq!(workers, [vec![0, 1, 2, 3]]);
+        }
+    }
+
+    #[tokio::test(crate = "hub_core::tokio")]
+    async fn key_is_handled_by_one_worker_in_order() {
+        let (processor, mut queues) = processor(Ordering::Key, 4);
+
+        for (offset, partition) in [0, 1, 2, 3].into_iter().enumerate() {
+            let mut msg = purchase(CREDIT, offset.try_into().unwrap());
+            msg.metadata.partition = partition;
+
+            processor.send(msg).await.unwrap();
+        }
+
+        let queued: Vec<_> = queued(&mut queues)
+            .into_iter()
+            .filter(|queued| !queued.is_empty())
+            .collect();
+
+        assert_eq!(queued, [vec![(0, 0), (1, 1), (2, 2), (3, 3)]]);
+    }
+
+    #[tokio::test(crate = "hub_core::tokio")]
+    async fn message_missing_its_parent_is_parked() {
+        let db: Connection = mock()
+            .append_exec_results([
+                MockExecResult::default(),
+                MockExecResult::default(),
+                MockExecResult::default(),
+            ])
+            .append_query_results([Vec::<pending_events::Model>::new()])
+            .append_query_results([Vec::<organizations::Model>::new()])
+            .append_exec_results([MockExecResult::default()])
+            .into_connection()
+            .into();
+
+        handle(&purchase(CREDIT, 0), &db).await.unwrap();
+
+        let log = db.into_inner().unwrap().into_transaction_log();
+        assert!(ran(&log, r#"INSERT INTO "pending_events""#));
+        assert!(!ran(&log, r#"INSERT INTO "credits""#));
+    }
+
+    #[tokio::test(crate = "hub_core::tokio")]
+    async fn message_behind_a_parked_one_of_its_key_is_parked_unprocessed() {
+        let earlier = purchase(CREDIT, 0);
+        let parked = pending_events::Model {
+            topic: earlier.metadata.topic,
+            partition: earlier.metadata.partition,
+            offset: earlier.metadata.offset,
+            key: earlier.key,
+            payload: earlier.payload,
+            parent_table: "organizations".to_string(),
+            parent_id: ORGANIZATION.parse().unwrap(),
+            timestamp: earlier.metadata.timestamp,
+            created_at: timestamp(),
+        };
+        let db: Connection = mock()
+            .append_exec_results([
+                MockExecResult::default(),
+                MockExecResult::default(),
+                MockExecResult::default(),
+            ])
+            .append_query_results([[parked]])
+            .append_exec_results([MockExecResult::default()])
+            .into_connection()
+            .into();
+
+        handle(&purchase(CREDIT, 1), &db).await.unwrap();
+
+        let log = db.into_inner().unwrap().into_transaction_log();
+        assert!(ran(&log, r#"INSERT INTO "pending_events""#));
+        assert!(!ran(&log, r#"FROM "organizations""#));
+    }
+}
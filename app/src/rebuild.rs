@@ -0,0 +1,905 @@
+use std::collections::{BTreeSet, HashSet, VecDeque};
+
+use hub_core::{clap, prelude::*, uuid::Uuid};
+use sea_orm::{
+    prelude::*,
+    sea_query::{DeleteStatement, Expr, SelectStatement},
+    Condition, ConnectionTrait, DatabaseTransaction, FromQueryResult, QueryOrder, QuerySelect,
+    QueryTrait, TransactionTrait,
+};
+
+use crate::{
+    db::Connection,
+    entities::{
+        collection_history, collections, consumer_offsets, credentials, credits, customers,
+        dead_letters, event_log, invites, member_status_changes, members, mint_status_changes,
+        mints, organizations, pending_events, projects, transfers, treasuries, wallets, webhooks,
+    },
+    events::{self, MissingParent, Owner},
+    Envelope,
+};
+
+/// Arguments for the `rebuild` command
+#[derive(Debug, clap::Args)]
+pub struct RebuildArgs {
+    /// Only rebuild the projections of this organization and its projects
+    #[arg(long, conflicts_with = "project")]
+    pub organization: Option<Uuid>,
+    /// Only rebuild the projections of this project
+    #[arg(long)]
+    pub project: Option<Uuid>,
+    /// Replay the journaled messages of this topic partition over the current projections
+    /// instead of truncating them and replaying the whole journal
+    #[arg(long, requires = "journal_partition")]
+    pub journal_topic: Option<String>,
+    /// Partition of `journal_topic` to replay
+    #[arg(long, requires = "journal_topic")]
+    pub journal_partition: Option<i32>,
+    /// First offset of the partition replayed from the journal. Nothing is read from Kafka, so
+    /// offsets that were never journaled are not replayed.
+    #[arg(long, requires = "journal_topic")]
+    pub journal_from: Option<i64>,
+    /// Last offset of the partition replayed from the journal
+    #[arg(long, requires = "journal_topic")]
+    pub journal_to: Option<i64>,
+}
+
+/// The projections a rebuild applies to
+#[derive(Debug, Clone, Copy)]
+pub enum Scope {
+    All,
+    Organization(Uuid),
+    Project(Uuid),
+}
+
+/// Row count of a projection table before and after a rebuild
+#[derive(Debug, Clone)]
+pub struct Discrepancy {
+    pub table: String,
+    pub before: i64,
+    pub after: i64,
+}
+
+/// Outcome of a [`run`]
+#[derive(Debug, Default, Clone)]
+pub struct Rebuilt {
+    pub truncated: u64,
+    /// Parked and dead lettered messages in scope, removed along with the projections
+    pub cleared: u64,
+    pub replayed: u64,
+    pub skipped: u64,
+    pub failed: u64,
+    pub discrepancies: Vec<Discrepancy>,
+}
+
+/// Number of journal entries loaded at a time
+const PAGE_SIZE: u64 = 500;
+
+/// Recomputes the projections in scope from the messages stored in the
+/// [`journal`](crate::journal), through the same [`events::process`] the consumer uses.
+///
+/// Without a journal topic the projections in scope are truncated and the whole journal is
+/// replayed. Parked and dead lettered messages in scope are removed too, as they are journaled
+/// and replayed with the rest. Since messages that were never journaled can't be replayed,
+/// nothing is truncated unless the journal of every partition starts at or before the first
+/// offset consumed from it. With a journal topic only the given offset range of that partition
+/// is replayed on top of the current projections. The consumer should be stopped while a rebuild
+/// runs.
+///
+/// Each partition is replayed in offset order, the order the consumer applied it in, and
+/// partitions are merged by the event time of their next message.
+///
+/// Messages still missing a parent after the other messages have been replayed, or failing for
+/// any other reason, are logged and counted but not dead lettered. Row counts that changed are
+/// reported as discrepancies.
+///
+/// # Errors
+/// This function fails if the journal doesn't cover the topics, the projections cannot be
+/// truncated or the journal cannot be read
+pub async fn run(db: &Connection, args: RebuildArgs) -> Result<Rebuilt> {
+    let RebuildArgs {
+        organization,
+        project,
+        journal_topic,
+        journal_partition,
+        journal_from,
+        journal_to,
+    } = args;
+
+    let scope = match (organization, project) {
+        (Some(id), _) => Scope::Organization(id),
+        (None, Some(id)) => Scope::Project(id),
+        (None, None) => Scope::All,
+    };
+
+    let mut projects = scope.projects(db).await?;
+    let before = counts(db, scope).await?;
+    let mut rebuilt = Rebuilt::default();
+
+    let mut range = Condition::all();
+
+    if let Some(topic) = journal_topic {
+        range = range
+            .add(event_log::Column::Topic.eq(topic))
+            .add(event_log::Column::Partition.eq(journal_partition))
+            .add(event_log::Column::Offset.gte(journal_from.unwrap_or(0)))
+            .add(event_log::Column::Offset.lte(journal_to.unwrap_or(i64::MAX)));
+    } else {
+        ensure_journaled(db).await?;
+
+        (rebuilt.truncated, rebuilt.cleared) = truncate(db, scope, &mut projects).await?;
+        info!(
+            ?scope,
+            rows = rebuilt.truncated,
+            messages = rebuilt.cleared,
+            "truncated projections"
+        );
+    }
+
+    let mut journal = Journal::open(db, range).await?;
+    let mut parked = Vec::new();
+    let mut read = 0;
+
+    while let Some(entry) = journal.next(db).await? {
+        let msg = Envelope::from(entry);
+        read += 1;
+
+        if scope.covers(&msg, &mut projects) {
+            match events::process(&msg, db.clone()).await {
+                Ok(()) => rebuilt.replayed += 1,
+                Err(e) if e.is::<MissingParent>() => parked.push(msg),
+                Err(e) => {
+                    warn!(
+                        topic = msg.metadata.topic,
+                        offset = msg.metadata.offset,
+                        "failed to replay message: {e:#}"
+                    );
+                    rebuilt.failed += 1;
+                },
+            }
+        } else {
+            rebuilt.skipped += 1;
+        }
+
+        if read % PAGE_SIZE == 0 || read == journal.total {
+            info!(
+                replayed = rebuilt.replayed,
+                skipped = rebuilt.skipped,
+                parked = parked.len(),
+                failed = rebuilt.failed,
+                total = journal.total,
+                "rebuilding projections"
+            );
+        }
+    }
+
+    replay_parked(db, parked, &mut rebuilt).await;
+
+    let after = counts(db, scope).await?;
+    rebuilt.discrepancies = before
+        .into_iter()
+        .zip(after)
+        .filter(|((_, before), (_, after))| before != after)
+        .map(|((table, before), (_, after))| Discrepancy {
+            table,
+            before,
+            after,
+        })
+        .collect();
+
+    for Discrepancy {
+        table,
+        before,
+        after,
+    } in &rebuilt.discrepancies
+    {
+        warn!(table, before, after, "row count changed after rebuild");
+    }
+
+    Ok(rebuilt)
+}
+
+/// The journal entries in a range, each partition in offset order and the partitions merged by
+/// the event time of their next entry. Entries with the same event time are taken by topic and
+/// partition.
+struct Journal {
+    heads: Vec<(Cursor, Option<event_log::Model>)>,
+    total: u64,
+}
+
+/// Number of entries of a partition in the range
+#[derive(Debug, FromQueryResult)]
+struct PartitionSize {
+    topic: String,
+    partition: i32,
+    count: i64,
+}
+
+impl Journal {
+    async fn open(db: &Connection, range: Condition) -> Result<Self> {
+        let partitions = event_log::Entity::find()
+            .select_only()
+            .column(event_log::Column::Topic)
+            .column(event_log::Column::Partition)
+            .column_as(Expr::col(event_log::Column::Offset).count(), "count")
+            .filter(range.clone())
+            .group_by(event_log::Column::Topic)
+            .group_by(event_log::Column::Partition)
+            .order_by_asc(event_log::Column::Topic)
+            .order_by_asc(event_log::Column::Partition)
+            .into_model::<PartitionSize>()
+            .all(db.get())
+            .await?;
+
+        let mut journal = Self {
+            heads: Vec::with_capacity(partitions.len()),
+            total: 0,
+        };
+
+        for PartitionSize {
+            topic,
+            partition,
+            count,
+        } in partitions
+        {
+            let mut cursor = Cursor {
+                topic,
+                partition,
+                range: range.clone(),
+                page: VecDeque::new(),
+                next: i64::MIN,
+                exhausted: false,
+            };
+            let head = cursor.next(db).await?;
+
+            journal.total += u64::try_from(count)?;
+            journal.heads.push((cursor, head));
+        }
+
+        Ok(journal)
+    }
+
+    async fn next(&mut self, db: &Connection) -> Result<Option<event_log::Model>> {
+        let Some((cursor, head)) = self
+            .heads
+            .iter_mut()
+            .filter_map(|(cursor, head)| {
+                let timestamp = head.as_ref()?.timestamp;
+
+                Some((timestamp, cursor, head))
+            })
+            .min_by_key(|(timestamp, ..)| *timestamp)
+            .map(|(_, cursor, head)| (cursor, head))
+        else {
+            return Ok(None);
+        };
+
+        let entry = head.take();
+        *head = cursor.next(db).await?;
+
+        Ok(entry)
+    }
+}
+
+/// The entries of one partition in a range, read in offset order a page at a time
+struct Cursor {
+    topic: String,
+    partition: i32,
+    range: Condition,
+    page: VecDeque<event_log::Model>,
+    /// The offset after the last entry read
+    next: i64,
+    exhausted: bool,
+}
+
+impl Cursor {
+    async fn next(&mut self, db: &Connection) -> Result<Option<event_log::Model>> {
+        if self.page.is_empty() && !self.exhausted {
+            let page = event_log::Entity::find()
+                .filter(self.range.clone())
+                .filter(event_log::Column::Topic.eq(self.topic.as_str()))
+                .filter(event_log::Column::Partition.eq(self.partition))
+                .filter(event_log::Column::Offset.gte(self.next))
+                .order_by_asc(event_log::Column::Offset)
+                .limit(PAGE_SIZE)
+                .all(db.get())
+                .await?;
+
+            self.exhausted = u64::try_from(page.len())? < PAGE_SIZE;
+
+            if let Some(last) = page.last() {
+                self.next = last.offset + 1;
+            }
+
+            self.page.extend(page);
+        }
+
+        Ok(self.page.pop_front())
+    }
+}
+
+/// Retries messages whose parent was missing until a pass applies none of them, since a parent
+/// published on another topic may carry a later timestamp than its children
+async fn replay_parked(db: &Connection, mut parked: Vec<Envelope>, rebuilt: &mut Rebuilt) {
+    while !parked.is_empty() {
+        let pending = parked.len();
+        let mut waiting = Vec::new();
+
+        for msg in parked {
+            match events::process(&msg, db.clone()).await {
+                Ok(()) => rebuilt.replayed += 1,
+                Err(e) if e.is::<MissingParent>() => waiting.push((msg, e)),
+                Err(e) => {
+                    warn!(
+                        topic = msg.metadata.topic,
+                        offset = msg.metadata.offset,
+                        "failed to replay message: {e:#}"
+                    );
+                    rebuilt.failed += 1;
+                },
+            }
+        }
+
+        if waiting.len() == pending {
+            for (msg, e) in waiting {
+                warn!(
+                    topic = msg.metadata.topic,
+                    offset = msg.metadata.offset,
+                    "failed to replay message: {e:#}"
+                );
+                rebuilt.failed += 1;
+            }
+
+            return;
+        }
+
+        parked = waiting.into_iter().map(|(msg, _)| msg).collect();
+    }
+}
+
+impl Scope {
+    /// Projects of the organization in scope, whose messages are replayed along with those of
+    /// the organization itself
+    async fn projects(self, db: &Connection) -> Result<HashSet<Uuid>> {
+        Ok(match self {
+            Self::Organization(id) => projects::Entity::find()
+                .filter(projects::Column::OrganizationId.eq(id))
+                .all(db.get())
+                .await?
+                .into_iter()
+                .map(|project| project.id)
+                .collect(),
+            Self::All | Self::Project(_) => HashSet::new(),
+        })
+    }
+
+    /// Whether `msg` belongs to the scope, going by the [`Owner`] its key and event refer to.
+    /// Messages that can't be decoded only belong to the whole database.
+    fn covers(self, msg: &Envelope, projects: &mut HashSet<Uuid>) -> bool {
+        if let Self::All = self {
+            return true;
+        }
+
+        msg.decode()
+            .is_ok_and(|decoded| self.owns(decoded.owner(), projects))
+    }
+
+    /// Whether rows of `owner` belong to the scope. Projects of the organization in scope are
+    /// added to `projects` as their messages are seen, so messages of a project created during
+    /// the replay are replayed too.
+    fn owns(self, owner: Owner, projects: &mut HashSet<Uuid>) -> bool {
+        match self {
+            Self::All => true,
+            Self::Project(id) => owner.project == Some(id),
+            Self::Organization(id) if owner.organization == Some(id) => {
+                projects.extend(owner.project);
+                true
+            },
+            Self::Organization(_) => owner
+                .project
+                .is_some_and(|project| projects.contains(&project)),
+        }
+    }
+
+    /// Rows keyed by organization, which no project scope covers
+    fn organization<C: ColumnTrait>(self, column: C) -> Option<Condition> {
+        match self {
+            Self::All => Some(Condition::all()),
+            Self::Organization(id) => Some(Condition::all().add(column.eq(id))),
+            Self::Project(_) => None,
+        }
+    }
+
+    /// Rows keyed by both an organization and a project
+    fn organization_or_project<O: ColumnTrait, P: ColumnTrait>(
+        self,
+        organization: O,
+        project: P,
+    ) -> Option<Condition> {
+        match self {
+            Self::Project(id) => Some(Condition::all().add(project.eq(id))),
+            _ => self.organization(organization),
+        }
+    }
+
+    /// Rows keyed by project, which belong to an organization through their project
+    fn project<C: ColumnTrait>(self, column: C) -> Condition {
+        match self {
+            Self::All => Condition::all(),
+            Self::Organization(id) => Condition::all().add(
+                column.in_subquery(
+                    projects::Entity::find()
+                        .select_only()
+                        .column(projects::Column::Id)
+                        .filter(projects::Column::OrganizationId.eq(id))
+                        .into_query(),
+                ),
+            ),
+            Self::Project(id) => Condition::all().add(column.eq(id)),
+        }
+    }
+}
+
+/// The rows of a projection table in scope
+struct Projection {
+    table: String,
+    count: SelectStatement,
+    delete: DeleteStatement,
+}
+
+impl Projection {
+    fn new<E: EntityTrait>(entity: E, condition: Option<Condition>) -> Option<Self> {
+        let condition = condition?;
+
+        Some(Self {
+            table: entity.table_name().to_string(),
+            count: E::find()
+                .select_only()
+                .column_as(Expr::cust("COUNT(*)"), "count")
+                .filter(condition.clone())
+                .into_query(),
+            delete: E::delete_many().filter(condition).into_query(),
+        })
+    }
+}
+
+/// Every projection table in `scope`, children before their parents
+fn projections(scope: Scope) -> Vec<Projection> {
+    [
+        Projection::new(
+            transfers::Entity,
+            Some(scope.project(transfers::Column::ProjectId)),
+        ),
+        Projection::new(
+            mint_status_changes::Entity,
+            Some(scope.project(mint_status_changes::Column::ProjectId)),
+        ),
+        Projection::new(mints::Entity, Some(scope.project(mints::Column::ProjectId))),
+        Projection::new(
+            collection_history::Entity,
+            Some(scope.project(collection_history::Column::ProjectId)),
+        ),
+        Projection::new(
+            collections::Entity,
+            Some(scope.project(collections::Column::ProjectId)),
+        ),
+        Projection::new(
+            treasuries::Entity,
+            Some(scope.project(treasuries::Column::ProjectId)),
+        ),
+        Projection::new(
+            wallets::Entity,
+            Some(scope.project(wallets::Column::ProjectId)),
+        ),
+        Projection::new(
+            customers::Entity,
+            Some(scope.project(customers::Column::ProjectId)),
+        ),
+        Projection::new(
+            webhooks::Entity,
+            scope.organization_or_project(
+                webhooks::Column::OrganizationId,
+                webhooks::Column::ProjectId,
+            ),
+        ),
+        Projection::new(
+            credentials::Entity,
+            scope.organization_or_project(
+                credentials::Column::OrganizationId,
+                credentials::Column::ProjectId,
+            ),
+        ),
+        Projection::new(
+            credits::Entity,
+            scope.organization(credits::Column::OrganizationId),
+        ),
+        Projection::new(
+            member_status_changes::Entity,
+            scope.organization(member_status_changes::Column::OrganizationId),
+        ),
+        Projection::new(
+            members::Entity,
+            scope.organization(members::Column::OrganizationId),
+        ),
+        Projection::new(
+            invites::Entity,
+            scope.organization(invites::Column::OrganizationId),
+        ),
+        Projection::new(
+            projects::Entity,
+            scope.organization_or_project(projects::Column::OrganizationId, projects::Column::Id),
+        ),
+        Projection::new(
+            organizations::Entity,
+            scope.organization(organizations::Column::Id),
+        ),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// Row counts of the projection tables in `scope`
+async fn counts(db: &Connection, scope: Scope) -> Result<Vec<(String, i64)>> {
+    let db = db.get();
+    let backend = db.get_database_backend();
+    let mut counts = Vec::new();
+
+    for Projection { table, count, .. } in projections(scope) {
+        let count = db
+            .query_one(backend.build(&count))
+            .await?
+            .map(|row| row.try_get::<i64>("", "count"))
+            .transpose()?
+            .unwrap_or_default();
+
+        counts.push((table, count));
+    }
+
+    Ok(counts)
+}
+
+/// The first offset of a partition found in the journal
+#[derive(Debug, FromQueryResult)]
+struct Journaled {
+    topic: String,
+    partition: i32,
+    first: i64,
+}
+
+/// Checks that the journal of every partition starts at or before the first offset consumed
+/// from it, so truncated projections can be recomputed from the journal alone. Offsets may be
+/// missing after that, Kafka transaction markers take up offsets which carry no message.
+async fn ensure_journaled(db: &Connection) -> Result<()> {
+    let consumed = consumer_offsets::Entity::find().all(db.get()).await?;
+    let journaled = event_log::Entity::find()
+        .select_only()
+        .column(event_log::Column::Topic)
+        .column(event_log::Column::Partition)
+        .column_as(Expr::col(event_log::Column::Offset).min(), "first")
+        .group_by(event_log::Column::Topic)
+        .group_by(event_log::Column::Partition)
+        .into_model::<Journaled>()
+        .all(db.get())
+        .await?;
+
+    let topics: BTreeSet<_> = events::TOPICS.iter().copied().collect();
+
+    for topic in topics {
+        if !journaled.iter().any(|journaled| journaled.topic == topic) {
+            bail!(
+                "refusing to truncate projections: nothing of {topic} is journaled, replay \
+                 partitions with --journal-topic instead"
+            );
+        }
+    }
+
+    for consumer_offsets::Model {
+        topic,
+        partition,
+        start_offset,
+    } in consumed
+    {
+        let first = journaled
+            .iter()
+            .find(|journaled| journaled.topic == topic && journaled.partition == partition)
+            .map(|journaled| journaled.first);
+
+        match first {
+            Some(first) if first <= start_offset => (),
+            Some(first) => bail!(
+                "refusing to truncate projections: {topic} partition {partition} is journaled \
+                 from offset {first} but was consumed from offset {start_offset}, replay \
+                 partitions with --journal-topic instead"
+            ),
+            None => bail!(
+                "refusing to truncate projections: {topic} partition {partition} was consumed \
+                 from offset {start_offset} but is not journaled, replay partitions with \
+                 --journal-topic instead"
+            ),
+        }
+    }
+
+    Ok(())
+}
+
+/// Deletes the rows of every projection table in `scope`, along with the parked and dead
+/// lettered messages in scope, in a single transaction. Returns the number of rows and messages
+/// deleted.
+async fn truncate(
+    db: &Connection,
+    scope: Scope,
+    projects: &mut HashSet<Uuid>,
+) -> Result<(u64, u64)> {
+    let tx = db.get().begin().await?;
+    let backend = tx.get_database_backend();
+    let mut deleted = 0;
+
+    for Projection { table, delete, .. } in projections(scope) {
+        let rows = tx.execute(backend.build(&delete)).await?.rows_affected();
+        debug!(table, rows, "truncated projection");
+
+        deleted += rows;
+    }
+
+    let cleared = clear_messages(&tx, scope, projects).await?;
+
+    tx.commit().await?;
+
+    Ok((deleted, cleared))
+}
+
+/// Deletes the parked and dead lettered messages in `scope`, which the replay parks or fails
+/// again from the journal when they still can't be applied
+async fn clear_messages(
+    tx: &DatabaseTransaction,
+    scope: Scope,
+    projects: &mut HashSet<Uuid>,
+) -> Result<u64> {
+    let mut cleared = 0;
+
+    for event in pending_events::Entity::find().all(tx).await? {
+        if scope.covers(&Envelope::from(event.clone()), projects) {
+            event.delete(tx).await?;
+            cleared += 1;
+        }
+    }
+
+    for letter in dead_letters::Entity::find().all(tx).await? {
+        if scope.covers(&Envelope::from(letter.clone()), projects) {
+            letter.delete(tx).await?;
+            cleared += 1;
+        }
+    }
+
+    Ok(cleared)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashSet};
+
+    use hub_core::{
+        chrono::{Duration, NaiveDateTime},
+        tokio,
+        uuid::Uuid,
+    };
+    use prost::Message;
+    use sea_orm::{Condition, DatabaseBackend, MockDatabase, Value};
+
+    use super::{ensure_journaled, Journal, Owner, Scope};
+    use crate::{
+        db::Connection,
+        entities::{consumer_offsets, event_log},
+        events,
+        proto::{
+            credential_events, nft_events, organization_events, Credential, CredentialEventKey,
+            CredentialEvents, MintTransfer, NftEventKey, NftEvents, OrganizationEventKey,
+            OrganizationEvents, Project,
+        },
+        Envelope, Metadata,
+    };
+
+    const ORGANIZATION: &str = "1d4c2a33-7a7e-4b8c-9c0e-6e7b0a2d8f10";
+    const PROJECT: &str = "2f6b5c44-8b8f-4c9d-8d1f-7f8c1b3e9a21";
+    const OTHER_PROJECT: &str = "3a7c6d55-9c90-4dae-9e20-809d2c4fab32";
+
+    fn id(id: &str) -> Uuid {
+        id.parse().unwrap()
+    }
+
+    fn envelope(topic: &str, key: &impl Message, payload: &impl Message) -> Envelope {
+        Envelope {
+            metadata: Metadata {
+                topic: topic.to_string(),
+                partition: 0,
+                offset: 0,
+                timestamp: NaiveDateTime::default(),
+            },
+            key: key.encode_to_vec(),
+            payload: payload.encode_to_vec(),
+        }
+    }
+
+    fn owner(msg: &Envelope) -> Owner {
+        msg.decode().unwrap().owner()
+    }
+
+    fn project_created() -> Envelope {
+        let key = OrganizationEventKey {
+            id: PROJECT.to_string(),
+            ..OrganizationEventKey::default()
+        };
+        let event = organization_events::Event::ProjectCreated(Project {
+            name: "Drops".to_string(),
+            organization_id: ORGANIZATION.to_string(),
+        });
+
+        envelope("hub-orgs", &key, &OrganizationEvents { event: Some(event) })
+    }
+
+    #[test]
+    fn projects_created_in_the_organization_are_in_scope() {
+        let scope = Scope::Organization(id(ORGANIZATION));
+        let mut projects = HashSet::new();
+
+        assert!(!scope.owns(Owner::project(PROJECT), &mut projects));
+        assert!(scope.owns(owner(&project_created()), &mut projects));
+        assert!(scope.owns(Owner::project(PROJECT), &mut projects));
+        assert!(!scope.owns(Owner::project(OTHER_PROJECT), &mut projects));
+    }
+
+    #[test]
+    fn organization_credentials_are_not_in_project_scope() {
+        let scope = Scope::Project(id(PROJECT));
+        let credential = |project_id: &str| {
+            let event = credential_events::Event::Created(Credential {
+                organization_id: ORGANIZATION.to_string(),
+                project_id: project_id.to_string(),
+            });
+
+            owner(&envelope(
+                "hub-credentials",
+                &CredentialEventKey::default(),
+                &CredentialEvents { event: Some(event) },
+            ))
+        };
+
+        assert!(!scope.owns(credential(""), &mut HashSet::new()));
+        assert!(scope.owns(credential(PROJECT), &mut HashSet::new()));
+    }
+
+    #[test]
+    fn ids_outside_the_owner_fields_are_ignored() {
+        let key = NftEventKey {
+            id: "4b8d7e66-ad01-4ebf-af31-91ae3d50bc43".to_string(),
+            project_id: OTHER_PROJECT.to_string(),
+            ..NftEventKey::default()
+        };
+        let event = nft_events::Event::TransferMint(MintTransfer {
+            mint_id: "5c9e8f77-be12-4fc0-b042-a2bf4e61cd54".to_string(),
+            sender: PROJECT.to_string(),
+            recipient: PROJECT.to_string(),
+        });
+        let msg = envelope("hub-nfts", &key, &NftEvents { event: Some(event) });
+
+        assert!(!Scope::Project(id(PROJECT)).covers(&msg, &mut HashSet::new()));
+        assert!(Scope::Project(id(OTHER_PROJECT)).covers(&msg, &mut HashSet::new()));
+    }
+
+    fn consumed(topic: &str, start_offset: i64) -> consumer_offsets::Model {
+        consumer_offsets::Model {
+            topic: topic.to_string(),
+            partition: 0,
+            start_offset,
+        }
+    }
+
+    fn journaled(topic: &str, first: i64) -> BTreeMap<&'static str, Value> {
+        BTreeMap::from([
+            ("topic", topic.into()),
+            ("partition", 0i32.into()),
+            ("first", first.into()),
+        ])
+    }
+
+    fn connection(
+        consumed: Vec<consumer_offsets::Model>,
+        journaled: Vec<BTreeMap<&'static str, Value>>,
+    ) -> Connection {
+        MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([consumed])
+            .append_query_results([journaled])
+            .into_connection()
+            .into()
+    }
+
+    fn topics() -> Vec<&'static str> {
+        events::TOPICS.iter().copied().collect()
+    }
+
+    #[tokio::test(crate = "hub_core::tokio")]
+    async fn truncation_requires_every_topic_journaled() {
+        let consumed = topics().into_iter().map(|t| consumed(t, 0)).collect();
+        let journaled = topics()
+            .into_iter()
+            .skip(1)
+            .map(|topic| journaled(topic, 0))
+            .collect();
+
+        let err = ensure_journaled(&connection(consumed, journaled))
+            .await
+            .unwrap_err()
+            .to_string();
+
+        assert!(err.contains(&format!("nothing of {} is journaled", topics()[0])));
+    }
+
+    #[tokio::test(crate = "hub_core::tokio")]
+    async fn truncation_requires_partitions_journaled_from_the_first_consumed_offset() {
+        let consumed = topics().into_iter().map(|t| consumed(t, 3)).collect();
+        let mut partitions: Vec<_> = topics().into_iter().map(|t| journaled(t, 3)).collect();
+        partitions[0] = journaled(topics()[0], 5);
+
+        let err = ensure_journaled(&connection(consumed, partitions))
+            .await
+            .unwrap_err()
+            .to_string();
+
+        assert!(err.contains("journaled from offset 5 but was consumed from offset 3"));
+    }
+
+    #[tokio::test(crate = "hub_core::tokio")]
+    async fn partitions_consumed_after_their_first_offset_are_journaled() {
+        let consumed = topics().into_iter().map(|t| consumed(t, 1200)).collect();
+        let journaled = topics().into_iter().map(|t| journaled(t, 1200)).collect();
+
+        ensure_journaled(&connection(consumed, journaled))
+            .await
+            .unwrap();
+    }
+
+    fn entry(partition: i32, offset: i64, timestamp: i64) -> event_log::Model {
+        let timestamp = NaiveDateTime::default() + Duration::seconds(timestamp);
+
+        event_log::Model {
+            topic: "hub-nfts".to_string(),
+            partition,
+            offset,
+            key: Vec::new(),
+            payload: Vec::new(),
+            decoded_key: None,
+            event: None,
+            decoded_payload: None,
+            timestamp,
+            received_at: timestamp,
+        }
+    }
+
+    #[tokio::test(crate = "hub_core::tokio")]
+    async fn journal_replays_partitions_in_offset_order_merged_by_event_time() {
+        let partition = |partition: i32, count: i64| {
+            BTreeMap::from([
+                ("topic", Value::from("hub-nfts")),
+                ("partition", partition.into()),
+                ("count", count.into()),
+            ])
+        };
+        let db: Connection = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([vec![partition(0, 2), partition(1, 1)]])
+            .append_query_results([vec![entry(0, 0, 20), entry(0, 1, 10)]])
+            .append_query_results([vec![entry(1, 0, 15)]])
+            .into_connection()
+            .into();
+
+        let mut journal = Journal::open(&db, Condition::all()).await.unwrap();
+        let mut replayed = Vec::new();
+
+        while let Some(entry) = journal.next(&db).await.unwrap() {
+            replayed.push((entry.partition, entry.offset));
+        }
+
+        assert_eq!(journal.total, 3);
+        assert_eq!(replayed, [(1, 0), (0, 0), (0, 1)]);
+    }
+}
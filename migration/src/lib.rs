@@ -8,6 +8,28 @@ mod m20230804_214701_create_mints_table;
 mod m20230805_140311_create_wallets_table;
 mod m20230818_030012_create_webhooks_table;
 mod m20230818_031112_create_credits_table;
+mod m20230901_142210_fix_webhooks_organization_fk;
+mod m20230904_093517_add_action_to_credits_table;
+mod m20230906_161043_create_dead_letters_table;
+mod m20230908_104455_create_pending_events_table;
+mod m20230911_135822_create_wallets_customer_id_index;
+mod m20230913_101204_add_status_to_mints_table;
+mod m20230913_102547_create_mint_status_changes_table;
+mod m20230915_143318_add_mint_and_wallets_to_transfers_table;
+mod m20230918_091427_add_status_and_supply_to_collections_table;
+mod m20230918_093052_create_collection_history_table;
+mod m20230920_110236_add_creator_metadata_to_collections_table;
+mod m20230922_150913_create_members_table;
+mod m20230925_101745_create_credentials_table;
+mod m20230927_083910_add_deleted_at_to_projects_customers_and_webhooks;
+mod m20230929_141122_add_kind_to_wallets_table;
+mod m20230929_143605_create_treasuries_table;
+mod m20231002_112948_create_event_log_table;
+mod m20231004_090211_create_pending_events_key_index;
+mod m20231006_101422_add_offset_to_collection_history_key;
+mod m20231009_093215_create_invites_and_member_status_changes_tables;
+mod m20231011_084512_add_offset_to_mint_status_changes_key;
+mod m20231012_093041_create_consumer_offsets_table;
 mod m20231804_024905_create_transfers_table;
 
 pub struct Migrator;
@@ -25,6 +47,28 @@ impl MigratorTrait for Migrator {
             Box::new(m20231804_024905_create_transfers_table::Migration),
             Box::new(m20230818_030012_create_webhooks_table::Migration),
             Box::new(m20230818_031112_create_credits_table::Migration),
+            Box::new(m20230901_142210_fix_webhooks_organization_fk::Migration),
+            Box::new(m20230904_093517_add_action_to_credits_table::Migration),
+            Box::new(m20230906_161043_create_dead_letters_table::Migration),
+            Box::new(m20230908_104455_create_pending_events_table::Migration),
+            Box::new(m20230911_135822_create_wallets_customer_id_index::Migration),
+            Box::new(m20230913_101204_add_status_to_mints_table::Migration),
+            Box::new(m20230913_102547_create_mint_status_changes_table::Migration),
+            Box::new(m20230915_143318_add_mint_and_wallets_to_transfers_table::Migration),
+            Box::new(m20230918_091427_add_status_and_supply_to_collections_table::Migration),
+            Box::new(m20230918_093052_create_collection_history_table::Migration),
+            Box::new(m20230920_110236_add_creator_metadata_to_collections_table::Migration),
+            Box::new(m20230922_150913_create_members_table::Migration),
+            Box::new(m20230925_101745_create_credentials_table::Migration),
+            Box::new(m20230927_083910_add_deleted_at_to_projects_customers_and_webhooks::Migration),
+            Box::new(m20230929_141122_add_kind_to_wallets_table::Migration),
+            Box::new(m20230929_143605_create_treasuries_table::Migration),
+            Box::new(m20231002_112948_create_event_log_table::Migration),
+            Box::new(m20231004_090211_create_pending_events_key_index::Migration),
+            Box::new(m20231006_101422_add_offset_to_collection_history_key::Migration),
+            Box::new(m20231009_093215_create_invites_and_member_status_changes_tables::Migration),
+            Box::new(m20231011_084512_add_offset_to_mint_status_changes_key::Migration),
+            Box::new(m20231012_093041_create_consumer_offsets_table::Migration),
         ]
     }
 }
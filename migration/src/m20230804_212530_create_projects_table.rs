@@ -55,4 +55,5 @@ pub enum Projects {
     Name,
     OrganizationId,
     Timestamp,
+    DeactivatedAt,
 }
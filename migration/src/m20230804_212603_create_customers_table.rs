@@ -58,4 +58,5 @@ pub enum Customers {
     Id,
     ProjectId,
     Timestamp,
+    DeletedAt,
 }
@@ -64,4 +64,9 @@ pub enum Collections {
     Blockchain,
     ProjectId,
     Timestamp,
+    Name,
+    Status,
+    Supply,
+    SellerFeeBasisPoints,
+    Creator,
 }
@@ -61,10 +61,11 @@ impl MigrationTrait for Migration {
 }
 
 #[derive(Iden)]
-enum Mints {
+pub enum Mints {
     Table,
     Id,
     CollectionId,
     ProjectId,
     Timestamp,
+    Status,
 }
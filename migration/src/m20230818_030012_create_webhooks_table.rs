@@ -66,4 +66,5 @@ pub enum Webhooks {
     ProjectId,
     OrganizationId,
     Timestamp,
+    DeletedAt,
 }
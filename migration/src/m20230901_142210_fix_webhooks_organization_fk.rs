@@ -0,0 +1,58 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{
+    m20230804_212412_create_organizations_table::Organizations,
+    m20230818_030012_create_webhooks_table::Webhooks,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_foreign_key(
+                ForeignKey::drop()
+                    .name("fk-webhooks_organization_id-organizations")
+                    .table(Webhooks::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .name("fk-webhooks_organization_id-organizations")
+                    .from(Webhooks::Table, Webhooks::OrganizationId)
+                    .to(Organizations::Table, Organizations::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .on_update(ForeignKeyAction::Cascade)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_foreign_key(
+                ForeignKey::drop()
+                    .name("fk-webhooks_organization_id-organizations")
+                    .table(Webhooks::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .name("fk-webhooks_organization_id-organizations")
+                    .from(Webhooks::Table, Webhooks::ProjectId)
+                    .to(Organizations::Table, Organizations::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .on_update(ForeignKeyAction::Cascade)
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,77 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(DeadLetters::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(DeadLetters::Topic).string().not_null())
+                    .col(ColumnDef::new(DeadLetters::Partition).integer().not_null())
+                    .col(ColumnDef::new(DeadLetters::Offset).big_integer().not_null())
+                    .primary_key(
+                        Index::create()
+                            .col(DeadLetters::Topic)
+                            .col(DeadLetters::Partition)
+                            .col(DeadLetters::Offset),
+                    )
+                    .col(ColumnDef::new(DeadLetters::Key).binary().not_null())
+                    .col(ColumnDef::new(DeadLetters::Payload).binary().not_null())
+                    .col(ColumnDef::new(DeadLetters::Error).text().not_null())
+                    .col(ColumnDef::new(DeadLetters::Attempts).integer().not_null())
+                    .col(
+                        ColumnDef::new(DeadLetters::Timestamp)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(DeadLetters::CreatedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(DeadLetters::UpdatedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                IndexCreateStatement::new()
+                    .name("dead_letters_created_at_idx")
+                    .table(DeadLetters::Table)
+                    .col(DeadLetters::CreatedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(DeadLetters::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum DeadLetters {
+    Table,
+    Topic,
+    Partition,
+    Offset,
+    Key,
+    Payload,
+    Error,
+    Attempts,
+    Timestamp,
+    CreatedAt,
+    UpdatedAt,
+}
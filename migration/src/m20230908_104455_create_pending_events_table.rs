@@ -0,0 +1,83 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(PendingEvents::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(PendingEvents::Topic).string().not_null())
+                    .col(
+                        ColumnDef::new(PendingEvents::Partition)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PendingEvents::Offset)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(PendingEvents::Topic)
+                            .col(PendingEvents::Partition)
+                            .col(PendingEvents::Offset),
+                    )
+                    .col(ColumnDef::new(PendingEvents::Key).binary().not_null())
+                    .col(ColumnDef::new(PendingEvents::Payload).binary().not_null())
+                    .col(
+                        ColumnDef::new(PendingEvents::ParentTable)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(PendingEvents::ParentId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(PendingEvents::Timestamp)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PendingEvents::CreatedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                IndexCreateStatement::new()
+                    .name("pending_events_created_at_idx")
+                    .table(PendingEvents::Table)
+                    .col(PendingEvents::CreatedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PendingEvents::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum PendingEvents {
+    Table,
+    Topic,
+    Partition,
+    Offset,
+    Key,
+    Payload,
+    ParentTable,
+    ParentId,
+    Timestamp,
+    CreatedAt,
+}
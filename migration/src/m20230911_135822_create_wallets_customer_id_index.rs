@@ -0,0 +1,34 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230805_140311_create_wallets_table::Wallets;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_index(
+                IndexCreateStatement::new()
+                    .name("wallets_customer_id_idx")
+                    .table(Wallets::Table)
+                    .col(Wallets::CustomerId)
+                    .index_type(IndexType::Hash)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("wallets_customer_id_idx")
+                    .table(Wallets::Table)
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,36 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230804_214701_create_mints_table::Mints;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Mints::Table)
+                    .add_column(
+                        ColumnDef::new(Mints::Status)
+                            .string()
+                            .not_null()
+                            .default("UNSPECIFIED"),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Mints::Table)
+                    .drop_column(Mints::Status)
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,93 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{
+    m20230804_212530_create_projects_table::Projects, m20230804_214701_create_mints_table::Mints,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(MintStatusChanges::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(MintStatusChanges::MintId).uuid().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-mint_status_changes_mint_id-mints")
+                            .from(MintStatusChanges::Table, MintStatusChanges::MintId)
+                            .to(Mints::Table, Mints::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .col(
+                        ColumnDef::new(MintStatusChanges::Status)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MintStatusChanges::Timestamp)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(MintStatusChanges::MintId)
+                            .col(MintStatusChanges::Status)
+                            .col(MintStatusChanges::Timestamp),
+                    )
+                    .col(
+                        ColumnDef::new(MintStatusChanges::CollectionId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MintStatusChanges::ProjectId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-mint_status_changes_project_id-projects")
+                            .from(MintStatusChanges::Table, MintStatusChanges::ProjectId)
+                            .to(Projects::Table, Projects::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                IndexCreateStatement::new()
+                    .name("mint_status_changes_project_id_idx")
+                    .table(MintStatusChanges::Table)
+                    .col(MintStatusChanges::ProjectId)
+                    .index_type(IndexType::Hash)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MintStatusChanges::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum MintStatusChanges {
+    Table,
+    MintId,
+    Status,
+    Timestamp,
+    CollectionId,
+    ProjectId,
+}
@@ -0,0 +1,100 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{
+    m20230804_213809_create_collections_table::Collections,
+    m20230804_214701_create_mints_table::Mints, m20231804_024905_create_transfers_table::Transfers,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transfers::Table)
+                    .add_column(ColumnDef::new(Transfers::MintId).uuid().null())
+                    .add_column(ColumnDef::new(Transfers::CollectionId).uuid().null())
+                    .add_column(ColumnDef::new(Transfers::Sender).string().null())
+                    .add_column(ColumnDef::new(Transfers::Recipient).string().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .name("fk-transfers_mint_id-mints")
+                    .from(Transfers::Table, Transfers::MintId)
+                    .to(Mints::Table, Mints::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .on_update(ForeignKeyAction::Cascade)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .name("fk-transfers_collection_id-collections")
+                    .from(Transfers::Table, Transfers::CollectionId)
+                    .to(Collections::Table, Collections::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .on_update(ForeignKeyAction::Cascade)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                IndexCreateStatement::new()
+                    .name("transfers_collection_id_idx")
+                    .table(Transfers::Table)
+                    .col(Transfers::CollectionId)
+                    .index_type(IndexType::Hash)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                IndexCreateStatement::new()
+                    .name("transfers_sender_idx")
+                    .table(Transfers::Table)
+                    .col(Transfers::Sender)
+                    .index_type(IndexType::Hash)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                IndexCreateStatement::new()
+                    .name("transfers_recipient_idx")
+                    .table(Transfers::Table)
+                    .col(Transfers::Recipient)
+                    .index_type(IndexType::Hash)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transfers::Table)
+                    .drop_column(Transfers::MintId)
+                    .drop_column(Transfers::CollectionId)
+                    .drop_column(Transfers::Sender)
+                    .drop_column(Transfers::Recipient)
+                    .to_owned(),
+            )
+            .await
+    }
+}
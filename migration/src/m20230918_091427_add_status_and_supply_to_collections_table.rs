@@ -0,0 +1,44 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230804_213809_create_collections_table::Collections;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Collections::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(Collections::Name)
+                            .string()
+                            .not_null()
+                            .default(""),
+                    )
+                    .add_column(
+                        ColumnDef::new(Collections::Status)
+                            .string()
+                            .not_null()
+                            .default("ACTIVE"),
+                    )
+                    .add_column(ColumnDef::new(Collections::Supply).big_integer().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Collections::Table)
+                    .drop_column(Collections::Status)
+                    .drop_column(Collections::Supply)
+                    .to_owned(),
+            )
+            .await
+    }
+}
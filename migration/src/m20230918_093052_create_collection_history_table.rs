@@ -0,0 +1,99 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{
+    m20230804_212530_create_projects_table::Projects,
+    m20230804_213809_create_collections_table::Collections,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(CollectionHistory::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(CollectionHistory::CollectionId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-collection_history_collection_id-collections")
+                            .from(CollectionHistory::Table, CollectionHistory::CollectionId)
+                            .to(Collections::Table, Collections::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .col(
+                        ColumnDef::new(CollectionHistory::Timestamp)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(CollectionHistory::CollectionId)
+                            .col(CollectionHistory::Timestamp),
+                    )
+                    .col(
+                        ColumnDef::new(CollectionHistory::ProjectId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-collection_history_project_id-projects")
+                            .from(CollectionHistory::Table, CollectionHistory::ProjectId)
+                            .to(Projects::Table, Projects::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .col(
+                        ColumnDef::new(CollectionHistory::Status)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(CollectionHistory::Name).string().not_null())
+                    .col(
+                        ColumnDef::new(CollectionHistory::Supply)
+                            .big_integer()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                IndexCreateStatement::new()
+                    .name("collection_history_project_id_idx")
+                    .table(CollectionHistory::Table)
+                    .col(CollectionHistory::ProjectId)
+                    .index_type(IndexType::Hash)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(CollectionHistory::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum CollectionHistory {
+    Table,
+    CollectionId,
+    Timestamp,
+    ProjectId,
+    Status,
+    Name,
+    Supply,
+}
@@ -0,0 +1,37 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230804_213809_create_collections_table::Collections;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Collections::Table)
+                    .add_column(
+                        ColumnDef::new(Collections::SellerFeeBasisPoints)
+                            .integer()
+                            .null(),
+                    )
+                    .add_column(ColumnDef::new(Collections::Creator).string().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Collections::Table)
+                    .drop_column(Collections::SellerFeeBasisPoints)
+                    .drop_column(Collections::Creator)
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,63 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230804_212412_create_organizations_table::Organizations;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Members::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Members::Id).uuid().not_null().primary_key())
+                    .col(ColumnDef::new(Members::OrganizationId).uuid().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-members_organization_id-organizations")
+                            .from(Members::Table, Members::OrganizationId)
+                            .to(Organizations::Table, Organizations::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .col(ColumnDef::new(Members::Status).string().not_null())
+                    .col(ColumnDef::new(Members::Timestamp).timestamp().not_null())
+                    .col(ColumnDef::new(Members::JoinedAt).timestamp().null())
+                    .col(ColumnDef::new(Members::DeactivatedAt).timestamp().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                IndexCreateStatement::new()
+                    .name("members_organization_id_idx")
+                    .table(Members::Table)
+                    .col(Members::OrganizationId)
+                    .index_type(IndexType::Hash)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Members::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Members {
+    Table,
+    Id,
+    OrganizationId,
+    Status,
+    Timestamp,
+    JoinedAt,
+    DeactivatedAt,
+}
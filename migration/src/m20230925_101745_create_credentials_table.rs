@@ -0,0 +1,85 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{
+    m20230804_212412_create_organizations_table::Organizations,
+    m20230804_212530_create_projects_table::Projects,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Credentials::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Credentials::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(Credentials::OrganizationId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-credentials_organization_id-organizations")
+                            .from(Credentials::Table, Credentials::OrganizationId)
+                            .to(Organizations::Table, Organizations::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .col(ColumnDef::new(Credentials::ProjectId).uuid().null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-credentials_project_id-projects")
+                            .from(Credentials::Table, Credentials::ProjectId)
+                            .to(Projects::Table, Projects::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .col(
+                        ColumnDef::new(Credentials::Timestamp)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Credentials::DeletedAt).timestamp().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                IndexCreateStatement::new()
+                    .name("credentials_organization_id_idx")
+                    .table(Credentials::Table)
+                    .col(Credentials::OrganizationId)
+                    .index_type(IndexType::Hash)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Credentials::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Credentials {
+    Table,
+    Id,
+    OrganizationId,
+    ProjectId,
+    Timestamp,
+    DeletedAt,
+}
@@ -0,0 +1,53 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Wallets::Table)
+                    .add_column(
+                        ColumnDef::new(Wallets::Kind)
+                            .string()
+                            .not_null()
+                            .default("CUSTOMER"),
+                    )
+                    .modify_column(ColumnDef::new(Wallets::CustomerId).uuid().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // project wallets have no customer and cannot be kept once it is required again
+        manager
+            .exec_stmt(
+                Query::delete()
+                    .from_table(Wallets::Table)
+                    .and_where(Expr::col(Wallets::CustomerId).is_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Wallets::Table)
+                    .drop_column(Wallets::Kind)
+                    .modify_column(ColumnDef::new(Wallets::CustomerId).uuid().not_null())
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum Wallets {
+    Table,
+    CustomerId,
+    Kind,
+}
@@ -0,0 +1,77 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{
+    m20230804_212530_create_projects_table::Projects,
+    m20230804_212603_create_customers_table::Customers,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Treasuries::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Treasuries::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Treasuries::ProjectId).uuid().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-treasuries_project_id-projects")
+                            .from(Treasuries::Table, Treasuries::ProjectId)
+                            .to(Projects::Table, Projects::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .col(ColumnDef::new(Treasuries::CustomerId).uuid().null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-treasuries_customer_id-customers")
+                            .from(Treasuries::Table, Treasuries::CustomerId)
+                            .to(Customers::Table, Customers::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .col(ColumnDef::new(Treasuries::Kind).string().not_null())
+                    .col(ColumnDef::new(Treasuries::Timestamp).timestamp().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                IndexCreateStatement::new()
+                    .name("treasuries_project_id_idx")
+                    .table(Treasuries::Table)
+                    .col(Treasuries::ProjectId)
+                    .index_type(IndexType::Hash)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Treasuries::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Treasuries {
+    Table,
+    Id,
+    ProjectId,
+    CustomerId,
+    Kind,
+    Timestamp,
+}
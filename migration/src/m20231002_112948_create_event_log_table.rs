@@ -0,0 +1,80 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(EventLog::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(EventLog::Topic).string().not_null())
+                    .col(ColumnDef::new(EventLog::Partition).integer().not_null())
+                    .col(ColumnDef::new(EventLog::Offset).big_integer().not_null())
+                    .primary_key(
+                        Index::create()
+                            .col(EventLog::Topic)
+                            .col(EventLog::Partition)
+                            .col(EventLog::Offset),
+                    )
+                    .col(ColumnDef::new(EventLog::Key).binary().not_null())
+                    .col(ColumnDef::new(EventLog::Payload).binary().not_null())
+                    .col(ColumnDef::new(EventLog::DecodedKey).json_binary().null())
+                    .col(ColumnDef::new(EventLog::Event).string().null())
+                    .col(
+                        ColumnDef::new(EventLog::DecodedPayload)
+                            .json_binary()
+                            .null(),
+                    )
+                    .col(ColumnDef::new(EventLog::Timestamp).timestamp().not_null())
+                    .col(ColumnDef::new(EventLog::ReceivedAt).timestamp().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                IndexCreateStatement::new()
+                    .name("event_log_timestamp_idx")
+                    .table(EventLog::Table)
+                    .col(EventLog::Timestamp)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                IndexCreateStatement::new()
+                    .name("event_log_event_idx")
+                    .table(EventLog::Table)
+                    .col(EventLog::Event)
+                    .index_type(IndexType::Hash)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(EventLog::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum EventLog {
+    Table,
+    Topic,
+    Partition,
+    Offset,
+    Key,
+    Payload,
+    DecodedKey,
+    Event,
+    DecodedPayload,
+    Timestamp,
+    ReceivedAt,
+}
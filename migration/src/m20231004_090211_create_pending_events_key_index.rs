@@ -0,0 +1,34 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230908_104455_create_pending_events_table::PendingEvents;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_index(
+                IndexCreateStatement::new()
+                    .name("pending_events_topic_key_idx")
+                    .table(PendingEvents::Table)
+                    .col(PendingEvents::Topic)
+                    .col(PendingEvents::Key)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("pending_events_topic_key_idx")
+                    .table(PendingEvents::Table)
+                    .to_owned(),
+            )
+            .await
+    }
+}
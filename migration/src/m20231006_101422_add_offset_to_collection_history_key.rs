@@ -0,0 +1,79 @@
+use sea_orm_migration::{
+    prelude::*,
+    sea_orm::{ConnectionTrait, Statement},
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CollectionHistory::Table)
+                    .add_column(
+                        ColumnDef::new(CollectionHistory::Offset)
+                            .big_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        execute(
+            manager,
+            r#"ALTER TABLE "collection_history" DROP CONSTRAINT "collection_history_pkey",
+               ADD PRIMARY KEY ("collection_id", "timestamp", "offset")"#,
+        )
+        .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // only the last of the changes sharing a timestamp fits the previous key
+        execute(
+            manager,
+            r#"DELETE FROM "collection_history" AS earlier USING "collection_history" AS later
+               WHERE earlier."collection_id" = later."collection_id"
+               AND earlier."timestamp" = later."timestamp"
+               AND earlier."offset" < later."offset""#,
+        )
+        .await?;
+
+        execute(
+            manager,
+            r#"ALTER TABLE "collection_history" DROP CONSTRAINT "collection_history_pkey",
+               ADD PRIMARY KEY ("collection_id", "timestamp")"#,
+        )
+        .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CollectionHistory::Table)
+                    .drop_column(CollectionHistory::Offset)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+async fn execute(manager: &SchemaManager<'_>, sql: &str) -> Result<(), DbErr> {
+    let db = manager.get_connection();
+
+    db.execute(Statement::from_string(
+        db.get_database_backend(),
+        sql.to_owned(),
+    ))
+    .await?;
+
+    Ok(())
+}
+
+#[derive(Iden)]
+enum CollectionHistory {
+    Table,
+    Offset,
+}
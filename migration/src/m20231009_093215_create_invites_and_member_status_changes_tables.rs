@@ -0,0 +1,160 @@
+use sea_orm_migration::{
+    prelude::*,
+    sea_orm::{ConnectionTrait, Statement},
+};
+
+use crate::{
+    m20230804_212412_create_organizations_table::Organizations,
+    m20230922_150913_create_members_table::Members,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Invites::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Invites::Id).uuid().not_null().primary_key())
+                    .col(ColumnDef::new(Invites::OrganizationId).uuid().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-invites_organization_id-organizations")
+                            .from(Invites::Table, Invites::OrganizationId)
+                            .to(Organizations::Table, Organizations::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .col(ColumnDef::new(Invites::Timestamp).timestamp().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                IndexCreateStatement::new()
+                    .name("invites_organization_id_idx")
+                    .table(Invites::Table)
+                    .col(Invites::OrganizationId)
+                    .index_type(IndexType::Hash)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        // pending invites were recorded as members keyed by the invite id
+        execute(
+            manager,
+            r#"INSERT INTO "invites" ("id", "organization_id", "timestamp")
+               SELECT "id", "organization_id", "timestamp" FROM "members"
+               WHERE "status" = 'INVITED'"#,
+        )
+        .await?;
+
+        execute(
+            manager,
+            r#"DELETE FROM "members" WHERE "status" = 'INVITED'"#,
+        )
+        .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(MemberStatusChanges::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(MemberStatusChanges::MemberId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-member_status_changes_member_id-members")
+                            .from(MemberStatusChanges::Table, MemberStatusChanges::MemberId)
+                            .to(Members::Table, Members::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .col(
+                        ColumnDef::new(MemberStatusChanges::Timestamp)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MemberStatusChanges::Offset)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(MemberStatusChanges::MemberId)
+                            .col(MemberStatusChanges::Timestamp)
+                            .col(MemberStatusChanges::Offset),
+                    )
+                    .col(
+                        ColumnDef::new(MemberStatusChanges::Status)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MemberStatusChanges::OrganizationId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MemberStatusChanges::Table).to_owned())
+            .await?;
+
+        execute(
+            manager,
+            r#"INSERT INTO "members" ("id", "organization_id", "status", "timestamp")
+               SELECT "id", "organization_id", 'INVITED', "timestamp" FROM "invites"
+               ON CONFLICT DO NOTHING"#,
+        )
+        .await?;
+
+        manager
+            .drop_table(Table::drop().table(Invites::Table).to_owned())
+            .await
+    }
+}
+
+async fn execute(manager: &SchemaManager<'_>, sql: &str) -> Result<(), DbErr> {
+    let db = manager.get_connection();
+
+    db.execute(Statement::from_string(
+        db.get_database_backend(),
+        sql.to_owned(),
+    ))
+    .await?;
+
+    Ok(())
+}
+
+#[derive(Iden)]
+pub enum Invites {
+    Table,
+    Id,
+    OrganizationId,
+    Timestamp,
+}
+
+#[derive(Iden)]
+pub enum MemberStatusChanges {
+    Table,
+    MemberId,
+    Timestamp,
+    Offset,
+    Status,
+    OrganizationId,
+}
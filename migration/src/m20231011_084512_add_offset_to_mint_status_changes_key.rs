@@ -0,0 +1,80 @@
+use sea_orm_migration::{
+    prelude::*,
+    sea_orm::{ConnectionTrait, Statement},
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MintStatusChanges::Table)
+                    .add_column(
+                        ColumnDef::new(MintStatusChanges::Offset)
+                            .big_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        execute(
+            manager,
+            r#"ALTER TABLE "mint_status_changes" DROP CONSTRAINT "mint_status_changes_pkey",
+               ADD PRIMARY KEY ("mint_id", "status", "timestamp", "offset")"#,
+        )
+        .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // only the last of the changes to a status sharing a timestamp fits the previous key
+        execute(
+            manager,
+            r#"DELETE FROM "mint_status_changes" AS earlier USING "mint_status_changes" AS later
+               WHERE earlier."mint_id" = later."mint_id"
+               AND earlier."status" = later."status"
+               AND earlier."timestamp" = later."timestamp"
+               AND earlier."offset" < later."offset""#,
+        )
+        .await?;
+
+        execute(
+            manager,
+            r#"ALTER TABLE "mint_status_changes" DROP CONSTRAINT "mint_status_changes_pkey",
+               ADD PRIMARY KEY ("mint_id", "status", "timestamp")"#,
+        )
+        .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MintStatusChanges::Table)
+                    .drop_column(MintStatusChanges::Offset)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+async fn execute(manager: &SchemaManager<'_>, sql: &str) -> Result<(), DbErr> {
+    let db = manager.get_connection();
+
+    db.execute(Statement::from_string(
+        db.get_database_backend(),
+        sql.to_owned(),
+    ))
+    .await?;
+
+    Ok(())
+}
+
+#[derive(Iden)]
+enum MintStatusChanges {
+    Table,
+    Offset,
+}
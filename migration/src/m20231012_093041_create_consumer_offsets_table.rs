@@ -0,0 +1,72 @@
+use sea_orm_migration::{
+    prelude::*,
+    sea_orm::{ConnectionTrait, Statement},
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ConsumerOffsets::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(ConsumerOffsets::Topic).string().not_null())
+                    .col(
+                        ColumnDef::new(ConsumerOffsets::Partition)
+                            .integer()
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(ConsumerOffsets::Topic)
+                            .col(ConsumerOffsets::Partition),
+                    )
+                    .col(
+                        ColumnDef::new(ConsumerOffsets::StartOffset)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // every message consumed so far was journaled before it was processed
+        execute(
+            manager,
+            r#"INSERT INTO "consumer_offsets" ("topic", "partition", "start_offset")
+               SELECT "topic", "partition", MIN("offset") FROM "event_log"
+               GROUP BY "topic", "partition""#,
+        )
+        .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ConsumerOffsets::Table).to_owned())
+            .await
+    }
+}
+
+async fn execute(manager: &SchemaManager<'_>, sql: &str) -> Result<(), DbErr> {
+    let db = manager.get_connection();
+
+    db.execute(Statement::from_string(
+        db.get_database_backend(),
+        sql.to_owned(),
+    ))
+    .await?;
+
+    Ok(())
+}
+
+#[derive(Iden)]
+enum ConsumerOffsets {
+    Table,
+    Topic,
+    Partition,
+    StartOffset,
+}
@@ -54,9 +54,13 @@ impl MigrationTrait for Migration {
 }
 
 #[derive(Iden)]
-enum Transfers {
+pub enum Transfers {
     Table,
     Id,
     ProjectId,
     Timestamp,
+    MintId,
+    CollectionId,
+    Sender,
+    Recipient,
 }